@@ -1,10 +1,102 @@
-use crate::tiles::tile_kind::TileKind;
+use crate::tile::Tile;
 
 #[derive(Clone, Debug)]
 pub enum Action {
     None,
     Destroy(usize),     // remove target tile
-    Replace(usize, TileKind), // replace target with new kind
+    Replace(usize, Tile), // replace target with new tile
     Swap(usize, usize), // swap two tiles
-    SwapCrossChunk(usize, (i32, i32), usize, TileKind), // swap two tiles across chunks
-}
\ No newline at end of file
+    Move(usize, usize, (f32, f32)), // swap two tiles and set the velocity of the one that moved
+    SetVelocity(usize, (f32, f32)),
+    SwapCrossChunk(usize, (i32, i32), usize, Tile), // swap two tiles across chunks
+    ReplaceCrossChunk((i32, i32), usize, Tile), // replace a tile in a neighbouring chunk
+    React(usize, Tile, usize, Tile), // replace two tiles at once
+    ReactCrossChunk(usize, Tile, (i32, i32), usize, Tile), // replace two tiles across chunks
+}
+
+impl Action {
+    // Tiles the action writes to, as (chunk, index) pairs, for an action
+    // produced by the chunk at `coord`
+    pub fn targets(&self, coord: (i32, i32)) -> [Option<((i32, i32), usize)>; 2] {
+        match *self {
+            Action::None => [None, None],
+            Action::Destroy(idx) | Action::Replace(idx, _) | Action::SetVelocity(idx, _) => {
+                [Some((coord, idx)), None]
+            }
+            Action::Swap(idx_a, idx_b)
+            | Action::Move(idx_a, idx_b, _)
+            | Action::React(idx_a, _, idx_b, _) => [Some((coord, idx_a)), Some((coord, idx_b))],
+            Action::SwapCrossChunk(idx_a, neighbor_coord, idx_b, _)
+            | Action::ReactCrossChunk(idx_a, _, neighbor_coord, idx_b, _) => {
+                [Some((coord, idx_a)), Some((neighbor_coord, idx_b))]
+            }
+            Action::ReplaceCrossChunk(neighbor_coord, idx, _) => [Some((neighbor_coord, idx)), None],
+        }
+    }
+
+    // Where the acting tile ended up, for actions that move it into a
+    // neighbouring chunk
+    pub fn moved_to(&self) -> Option<((i32, i32), usize)> {
+        match *self {
+            Action::SwapCrossChunk(_, neighbor_coord, idx, _) => Some((neighbor_coord, idx)),
+            _ => None,
+        }
+    }
+
+    // Swaps `idx` with a target located by tile_checks, which may sit in a neighbouring chunk
+    pub fn swap_with(
+        idx: usize,
+        (target_idx, cross_chunk, target_chunk): (usize, bool, (i32, i32)),
+        this_tile: Tile,
+    ) -> Action {
+        if cross_chunk {
+            Action::SwapCrossChunk(idx, target_chunk, target_idx, this_tile)
+        } else {
+            Action::Swap(idx, target_idx)
+        }
+    }
+
+    // Like swap_with, but the moving tile also picks up a new velocity
+    pub fn move_to(
+        idx: usize,
+        (target_idx, cross_chunk, target_chunk): (usize, bool, (i32, i32)),
+        this_tile: Tile,
+        velocity: (f32, f32),
+    ) -> Action {
+        if cross_chunk {
+            let moved = Tile {
+                velocity,
+                ..this_tile
+            };
+            Action::SwapCrossChunk(idx, target_chunk, target_idx, moved)
+        } else {
+            Action::Move(idx, target_idx, velocity)
+        }
+    }
+
+    // Replaces a target located by tile_checks, which may sit in a neighbouring chunk
+    pub fn replace_at(
+        (target_idx, cross_chunk, target_chunk): (usize, bool, (i32, i32)),
+        tile: Tile,
+    ) -> Action {
+        if cross_chunk {
+            Action::ReplaceCrossChunk(target_chunk, target_idx, tile)
+        } else {
+            Action::Replace(target_idx, tile)
+        }
+    }
+
+    // Replaces `idx` and a target located by tile_checks in the same step
+    pub fn react(
+        idx: usize,
+        product: Tile,
+        (target_idx, cross_chunk, target_chunk): (usize, bool, (i32, i32)),
+        target_product: Tile,
+    ) -> Action {
+        if cross_chunk {
+            Action::ReactCrossChunk(idx, product, target_chunk, target_idx, target_product)
+        } else {
+            Action::React(idx, product, target_idx, target_product)
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// Runs the simulation without a window, for benchmarks and batch runs on
+// machines without a display. Builds with `--no-default-features`.
+
+use rust_procedural_world_generation::{
+    chunk_list::ChunkList, materials, ppm, save, tiles::tile_kind::TileKind,
+    world_gen::GeneratorKind,
+};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+const CHUNK_SIZE: usize = 32;
+
+struct Options {
+    generator: GeneratorKind,
+    seed: u64,
+    materials: Option<PathBuf>,
+    load: Option<PathBuf>,
+    size: (i32, i32), // chunks across and down for a generated world
+    ticks: u64,
+    image: Option<PathBuf>,
+    save: Option<PathBuf>,
+}
+
+const USAGE: &str = "usage: headless [--generator empty|soup|terrain] [--seed <u64>] \
+[--materials <file>] [--load <save file>] [--size <chunks>x<chunks>] [--ticks <n>] \
+[--image <file.ppm>] [--save <file>]";
+
+impl Options {
+    // Unlike the windowed app, any argument that doesn't parse is an error, so
+    // a typo in a batch job fails instead of running with the defaults
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            generator: GeneratorKind::Terrain,
+            seed: 1337,
+            materials: None,
+            load: None,
+            size: (8, 8),
+            ticks: 1000,
+            image: None,
+            save: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--generator" => match args.next().as_deref().and_then(GeneratorKind::from_name) {
+                    Some(kind) => options.generator = kind,
+                    None => return Err("expected one of empty|soup|terrain after --generator".into()),
+                },
+                "--seed" => match args.next().and_then(|s| s.parse().ok()) {
+                    Some(seed) => options.seed = seed,
+                    None => return Err("expected an unsigned integer after --seed".into()),
+                },
+                "--materials" => options.materials = Some(path_after(&arg, args.next())?),
+                "--load" => options.load = Some(path_after(&arg, args.next())?),
+                "--size" => match args.next().as_deref().and_then(parse_size) {
+                    Some(size) => options.size = size,
+                    None => return Err("expected <chunks>x<chunks>, e.g. 8x8, after --size".into()),
+                },
+                "--ticks" => match args.next().and_then(|s| s.parse().ok()) {
+                    Some(ticks) => options.ticks = ticks,
+                    None => return Err("expected an unsigned integer after --ticks".into()),
+                },
+                "--image" => options.image = Some(path_after(&arg, args.next())?),
+                "--save" => options.save = Some(path_after(&arg, args.next())?),
+                other => return Err(format!("unknown argument {other}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn path_after(flag: &str, value: Option<String>) -> Result<PathBuf, String> {
+    value
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("expected a file path after {flag}"))
+}
+
+fn parse_size(size: &str) -> Option<(i32, i32)> {
+    let (across, down) = size.split_once('x')?;
+    let size = (across.parse().ok()?, down.parse().ok()?);
+    (size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+fn main() {
+    let options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(error) = materials::init_from(options.materials.as_deref()) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+
+    let (mut world, generator) = match &options.load {
+        Some(path) => match save::load(path) {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                eprintln!("{error}");
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let (across, down) = options.size;
+            let world = ChunkList::new(
+                CHUNK_SIZE,
+                CHUNK_SIZE,
+                across,
+                down,
+                options.generator.create(options.seed),
+                options.seed,
+            );
+            (world, options.generator)
+        }
+    };
+
+    // Without a viewport to follow, keep simulating the area we started with
+    let Some(((min_x, min_y), (max_x, max_y))) = world.bounds() else {
+        eprintln!("the world has no chunks");
+        std::process::exit(1);
+    };
+    let (chunk_width, chunk_height) = (world.chunk_width as isize, world.chunk_height as isize);
+    let keep_x = min_x as isize * chunk_width;
+    let keep_y = min_y as isize * chunk_height;
+    let keep_width = (max_x - min_x) as usize * world.chunk_width;
+    let keep_height = (max_y - min_y) as usize * world.chunk_height;
+
+    println!(
+        "{} chunks of {}x{} tiles, starting at tick {}",
+        world.iter().count(),
+        world.chunk_width,
+        world.chunk_height,
+        world.tick()
+    );
+
+    let started = Instant::now();
+    let mut slowest = Duration::ZERO;
+    for _ in 0..options.ticks {
+        let tick_started = Instant::now();
+        world.update();
+        world.cull_chunks(keep_x, keep_y, keep_width, keep_height, 0);
+        slowest = slowest.max(tick_started.elapsed());
+    }
+    let elapsed = started.elapsed();
+
+    let ticks = options.ticks.max(1) as f64;
+    println!(
+        "ran {} ticks in {:.2}s: {:.3} ms per tick on average, {:.3} ms at worst, {:.1} ticks per second",
+        options.ticks,
+        elapsed.as_secs_f64(),
+        elapsed.as_secs_f64() * 1000.0 / ticks,
+        slowest.as_secs_f64() * 1000.0,
+        ticks / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    println!("awake chunks: {}", world.awake_chunks());
+
+    let mut counts: HashMap<TileKind, usize> = HashMap::new();
+    for (_, chunk) in world.iter() {
+        for tile in &chunk.tiles {
+            *counts.entry(tile.kind).or_insert(0) += 1;
+        }
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.0.cmp(&b.0.0)));
+    println!("tiles:");
+    for (kind, count) in counts {
+        println!("  {:<12} {count}", kind.name());
+    }
+
+    if let Some(path) = &options.image {
+        if let Err(error) = ppm::write_ppm(path, &world) {
+            eprintln!("could not write {}: {error}", path.display());
+            std::process::exit(1);
+        }
+        println!("wrote {}", path.display());
+    }
+    if let Some(path) = &options.save {
+        if let Err(error) = save::save(path, &world, generator) {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+        println!("wrote {}", path.display());
+    }
+}
@@ -1,9 +1,33 @@
-use crate::{action::Action, tiles::tile_kind::TileKind};
-use rand::prelude::*;
+use std::collections::HashSet;
+
+use crate::{
+    action::Action,
+    gravity::Gravity,
+    reactions::{can_react, react},
+    rng::TileRng,
+    tile::{AMBIENT_TEMPERATURE, Tile},
+    tile_checks::{offset_tile, tile_at},
+    tiles::{empty::Empty, tile_kind::TileKind},
+};
+
+// Temperature changes smaller than this per step let a chunk fall asleep
+const RESTING_HEAT_FLOW: f32 = 0.01;
+
+// Inclusive bounds of the tiles a chunk simulates on its next step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DirtyRect {
+    pub min_x: usize,
+    pub min_y: usize,
+    pub max_x: usize,
+    pub max_y: usize,
+}
+
+// A tile in a neighbouring chunk: the chunk's coordinate and the tile's index
+pub type NeighborTile = ((i32, i32), usize);
 
 pub struct Chunk {
-    pub tiles: Vec<TileKind>, // row-major order
-    dirty: bool,
+    pub tiles: Vec<Tile>,          // row-major order
+    dirty_rect: Option<DirtyRect>, // None while the chunk is asleep
     pub width: usize,
     pub height: usize,
     pub x: i32,
@@ -11,62 +35,140 @@ pub struct Chunk {
 }
 
 impl Chunk {
-    pub fn new(width: usize, height: usize, x: i32, y: i32, blank: bool) -> Self {
-        let dirty = true;
-
-        let mut tiles = Vec::with_capacity(width * height);
+    pub fn new(width: usize, height: usize, x: i32, y: i32, tiles: Vec<TileKind>) -> Self {
+        let tiles = tiles.into_iter().map(Tile::new).collect();
+        Self::from_tiles(width, height, x, y, tiles)
+    }
 
-        let mut rng = rand::rng();
+    // A chunk of tiles that already have their state, e.g. from a save file
+    pub fn from_tiles(width: usize, height: usize, x: i32, y: i32, tiles: Vec<Tile>) -> Self {
+        debug_assert_eq!(tiles.len(), width * height);
 
-        if !blank {
-            for _ in 0..(width * height) {
-                tiles.push(if rng.random_bool(0.2) {
-                    TileKind::GameOfLife
-                } else {
-                    TileKind::Empty
-                });
-            }
-        } else {
-            for _ in 0..(width * height) {
-                tiles.push(TileKind::Empty);
-            }
-        }
         Self {
             tiles,
             width,
             height,
             x,
             y,
-            dirty,
+            dirty_rect: Some(DirtyRect {
+                min_x: 0,
+                min_y: 0,
+                max_x: width - 1,
+                max_y: height - 1,
+            }),
+        }
+    }
+
+    // Places a fresh tile, resetting its lifetime
+    pub fn set_tile(&mut self, idx: usize, kind: TileKind) {
+        self.tiles[idx] = Tile::new(kind);
+    }
+
+    pub fn swap_tiles(&mut self, idx_a: usize, idx_b: usize) {
+        self.tiles.swap(idx_a, idx_b);
+    }
+
+    // Counts down every expiring tile by one step. Tiles at zero are left for
+    // their rules to remove.
+    pub fn age_tiles(&mut self) {
+        for tile in self.tiles.iter_mut() {
+            if tile.kind.lifetime().is_some() {
+                tile.lifetime = tile.lifetime.saturating_sub(1);
+            }
         }
     }
 
     pub fn is_dirty(&self) -> bool {
-        self.dirty
+        self.dirty_rect.is_some()
     }
 
-    pub fn mark_dirty(&mut self) {
-        self.dirty = true;
+    // Grows the dirty rectangle to cover a tile and the tiles next to it
+    pub fn mark_tile_dirty(&mut self, x: usize, y: usize) {
+        let min_x = x.saturating_sub(1);
+        let min_y = y.saturating_sub(1);
+        let max_x = (x + 1).min(self.width - 1);
+        let max_y = (y + 1).min(self.height - 1);
+        self.dirty_rect = Some(match self.dirty_rect {
+            Some(rect) => DirtyRect {
+                min_x: rect.min_x.min(min_x),
+                min_y: rect.min_y.min(min_y),
+                max_x: rect.max_x.max(max_x),
+                max_y: rect.max_y.max(max_y),
+            },
+            None => DirtyRect {
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            },
+        });
     }
 
-    pub fn mark_clean(&mut self) {
-        self.dirty = false;
+    // Hands out the rectangle to simulate now, leaving the chunk asleep until
+    // something marks it again
+    pub fn take_dirty_rect(&mut self) -> Option<DirtyRect> {
+        self.dirty_rect.take()
     }
 
+    // Actions for the tiles inside `rect`, plus the restless ones: tiles that
+    // may still change later without acting now, like a reaction that missed
+    // its roll or a gas counting down. Tiles that neither act nor are restless
+    // have settled and are skipped until something changes next to them.
+    // Every tile draws its randomness from its own world position and the
+    // step's `tick_key`, so the result doesn't depend on scheduling.
     pub fn update(
         &self,
         chunk_neighbors: &[&Chunk],
-    ) -> std::vec::Vec<Action> {
+        gravity: Gravity,
+        tick: u64,
+        tick_key: u64,
+        rect: DirtyRect,
+    ) -> (std::vec::Vec<Action>, Vec<usize>) {
         let mut actions = Vec::new();
+        let mut restless = Vec::new();
 
-        for y in 0..self.height {
-            for x in 0..self.width {
+        // When two tiles want the same spot the one scanned first wins, so the
+        // scan runs bottom-up every other step and each row flips direction
+        // from the one before, leaving no side favoured over time
+        let rows = rect.max_y - rect.min_y + 1;
+        let columns = rect.max_x - rect.min_x + 1;
+        for row in 0..rows {
+            let y = if tick.is_multiple_of(2) {
+                rect.min_y + row
+            } else {
+                rect.max_y - row
+            };
+            let right_to_left = (tick / 2 + y as u64) % 2 == 1;
+            for column in 0..columns {
+                let x = if right_to_left {
+                    rect.max_x - column
+                } else {
+                    rect.min_x + column
+                };
                 let idx = y * self.width + x;
-                let tile_kind = self.tiles[idx];
+                let tile_kind = self.tiles[idx].kind;
+
+                // Tiles that moved in from an earlier colour phase already had their turn
+                if self.tiles[idx].moved {
+                    continue;
+                }
+                let world_x = self.x as i64 * self.width as i64 + x as i64;
+                let world_y = self.y as i64 * self.height as i64 + y as i64;
+                let mut rng = TileRng::new(tick_key, world_x, world_y);
+
+                // reactions with neighbouring materials win over the usual rules
+                let action = react(x, y, self, chunk_neighbors, &mut rng);
+                if !matches!(action, Action::None) {
+                    actions.push(action);
+                    continue;
+                }
+                if tile_kind.lifetime().is_some() || can_react(x, y, self, chunk_neighbors) {
+                    restless.push(idx);
+                }
 
                 // run rules to get an action
                 for rule in tile_kind.rules() {
-                    let action = rule(x, y, self, chunk_neighbors);
+                    let action = rule(x, y, self, chunk_neighbors, gravity, &mut rng);
                     if !matches!(action, Action::None) {
                         actions.push(action.clone());
                         break; // only one action per tile per update
@@ -75,6 +177,95 @@ impl Chunk {
             }
         }
 
-        actions
+        (actions, restless)
+    }
+
+    // Temperature of every tile after exchanging heat with its four direct
+    // neighbours, including the ones across chunk borders. Each pair swaps the
+    // same amount of heat in opposite directions, so heat is only gained or
+    // lost through heat sources and open air cooling towards ambient.
+    // Neighbouring chunks outside `diffusing` won't take their share this
+    // step, so no heat flows to them either. Instead, the tiles there that
+    // would exchange enough heat to matter are returned to be woken up, and
+    // the heat flows once both sides are awake.
+    pub fn diffuse_heat(
+        &self,
+        chunk_neighbors: &[&Chunk],
+        diffusing: &HashSet<(i32, i32)>,
+    ) -> (Vec<f32>, Vec<NeighborTile>) {
+        let mut temperatures = Vec::with_capacity(self.tiles.len());
+        let mut warmed = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let tile = self.tiles[y * self.width + x];
+                if let Some(fixed) = tile.kind.fixed_temperature() {
+                    temperatures.push(fixed);
+                    continue;
+                }
+
+                let mut heat_flow = 0.0;
+                for (offset_x, offset_y) in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+                    let Some(target) =
+                        offset_tile(x, y, self, chunk_neighbors, offset_x, offset_y)
+                    else {
+                        continue;
+                    };
+                    let other = tile_at(self, chunk_neighbors, target);
+                    let conductivity = tile.kind.conductivity().min(other.kind.conductivity());
+                    let flow = conductivity * (other.temperature - tile.temperature);
+
+                    let (other_idx, cross_chunk, coord) = target;
+                    if cross_chunk && !diffusing.contains(&coord) {
+                        if flow.abs() > RESTING_HEAT_FLOW {
+                            warmed.push((coord, other_idx));
+                        }
+                        continue;
+                    }
+                    heat_flow += flow;
+                }
+
+                let mut temperature = tile.temperature + heat_flow / tile.kind.heat_capacity();
+                if tile.kind == TileKind::EMPTY {
+                    temperature += (AMBIENT_TEMPERATURE - temperature) * Empty::COOLING_RATE;
+                }
+                temperatures.push(temperature);
+            }
+        }
+
+        (temperatures, warmed)
+    }
+
+    // Stores the result of diffuse_heat and changes the phase of any tile that
+    // crossed one of its material's thresholds. Keeps tiles awake while their
+    // temperature is still moving, and returns the ones that changed phase.
+    pub fn apply_temperatures(&mut self, temperatures: &[f32]) -> Vec<usize> {
+        let mut phase_changed = Vec::new();
+        for (idx, &temperature) in temperatures.iter().enumerate() {
+            let tile = &mut self.tiles[idx];
+            let moving = (temperature - tile.temperature).abs() > RESTING_HEAT_FLOW;
+            tile.temperature = temperature;
+
+            if let Some(kind) = tile.kind.phase_change(temperature) {
+                // Fuel that ignites from heat alone burns as long as a lit one would
+                let lifetime = if kind == TileKind::FIRE {
+                    tile.kind.burn_time()
+                } else {
+                    kind.lifetime().unwrap_or(0)
+                };
+                *tile = Tile {
+                    kind,
+                    lifetime,
+                    temperature,
+                    velocity: (0.0, 0.0),
+                    moved: false,
+                };
+                phase_changed.push(idx);
+            }
+            if moving {
+                self.mark_tile_dirty(idx % self.width, idx / self.width);
+            }
+        }
+        phase_changed
     }
 }
@@ -1,10 +1,16 @@
 use crate::{
     action::Action,
-    chunk::{Chunk},
+    chunk::{Chunk, DirtyRect},
+    gravity::Gravity,
+    math::{div_floor, euclidean_mod},
+    rng::TileRng,
+    tile::Tile,
+    tile_checks::NEIGHBOR_OFFSETS,
     tiles::tile_kind::TileKind,
+    world_gen::WorldGenerator,
 };
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub type ChunkCoord = (i32, i32);
 
@@ -13,6 +19,11 @@ pub struct ChunkList {
     dead_chunks: HashMap<ChunkCoord, Chunk>,
     pub chunk_width: usize,
     pub chunk_height: usize,
+    pub gravity: Gravity,
+    generator: Box<dyn WorldGenerator>,
+    seed: u64,
+    tick: u64, // steps simulated so far
+    moved_tiles: Vec<(ChunkCoord, usize)>, // tiles flagged as moved this step
 }
 
 impl ChunkList {
@@ -21,13 +32,16 @@ impl ChunkList {
         chunk_height: usize,
         chunk_x_num: i32,
         chunk_y_num: i32,
+        generator: Box<dyn WorldGenerator>,
+        seed: u64,
     ) -> Self {
         let mut chunks = HashMap::new();
 
         for y in 0..chunk_y_num {
             for x in 0..chunk_x_num {
                 let coord = (x, y);
-                chunks.insert(coord, Chunk::new(chunk_width, chunk_height, x, y, true));
+                let tiles = generator.generate(coord, chunk_width, chunk_height);
+                chunks.insert(coord, Chunk::new(chunk_width, chunk_height, x, y, tiles));
             }
         }
 
@@ -36,9 +50,42 @@ impl ChunkList {
             dead_chunks: HashMap::new(),
             chunk_width,
             chunk_height,
+            gravity: Gravity::default(),
+            generator,
+            seed,
+            tick: 0,
+            moved_tiles: Vec::new(),
         }
     }
 
+    // A world restored from a save file. Every chunk starts awake.
+    pub fn from_chunks(
+        chunk_width: usize,
+        chunk_height: usize,
+        chunks: Vec<Chunk>,
+        generator: Box<dyn WorldGenerator>,
+        seed: u64,
+        tick: u64,
+    ) -> Self {
+        let mut list = Self::new(chunk_width, chunk_height, 0, 0, generator, seed);
+        list.alive_chunks = chunks
+            .into_iter()
+            .map(|chunk| ((chunk.x, chunk.y), chunk))
+            .collect();
+        list.tick = tick;
+        list
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Steps simulated so far
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    // Simulates one step of every awake chunk
     pub fn update(&mut self) {
         let dirty_coords: Vec<ChunkCoord> = self
             .alive_chunks
@@ -50,9 +97,14 @@ impl ChunkList {
         // Ensure neighbors exist for edge chunks
         self.extend_chunks(&dirty_coords);
 
-        let mut next_actions: HashMap<ChunkCoord, Vec<Action>> = HashMap::new();
+        let tick = self.tick;
+        let tick_key = TileRng::tick_key(self.seed, tick);
+        self.tick += 1;
 
-        // Process chunks in color groups (9-color scheme)
+        // Process chunks in color groups (9-color scheme). Chunks of one colour
+        // are at least three chunks apart, so they never read or write the same
+        // tiles and can run in parallel. Each phase is committed before the next
+        // one starts, so every chunk sees its neighbours as they are now.
         for color in 0..9 {
             let chunks_of_color: Vec<ChunkCoord> = dirty_coords
                 .iter()
@@ -64,72 +116,218 @@ impl ChunkList {
                 })
                 .collect();
 
+            // Each chunk only simulates the part that changed since its last
+            // step. Taking the rectangles leaves the chunks asleep unless this
+            // step changes something in or next to them again.
+            let rects: Vec<(ChunkCoord, DirtyRect)> = chunks_of_color
+                .iter()
+                .filter_map(|coord| {
+                    let rect = self.alive_chunks.get_mut(coord)?.take_dirty_rect()?;
+                    Some((*coord, rect))
+                })
+                .collect();
+
             // Phase 1: parallel compute next states (read-only)
-            let updates: Vec<(ChunkCoord, Vec<Action>)> = chunks_of_color
+            let updates: Vec<_> = rects
                 .par_iter()
-                .filter_map(|coord| {
-                    let neighbors = self.get_neighbors(*coord);
-                    if let Some(chunk) = self.alive_chunks.get(coord) {
-                        let new_tiles = chunk.update(&neighbors);
-                        Some((*coord, new_tiles))
+                .filter_map(|&(coord, rect)| {
+                    let neighbors = self.get_neighbors(coord);
+                    if let Some(chunk) = self.alive_chunks.get(&coord) {
+                        let new_tiles = chunk.update(&neighbors, self.gravity, tick, tick_key, rect);
+                        Some((coord, new_tiles))
                     } else {
                         None
                     }
                 })
                 .collect();
 
-            // Collect Actions and dirty chunks
-            for (coord, actions) in updates {
+            // Phase 2: apply them before the next colour reads its neighbours
+            let mut next_actions = HashMap::new();
+            for (coord, (actions, restless)) in updates {
+                if let Some(chunk) = self.alive_chunks.get_mut(&coord) {
+                    for idx in restless {
+                        chunk.mark_tile_dirty(idx % chunk.width, idx / chunk.width);
+                    }
+                }
                 next_actions.insert(coord, actions);
             }
+            self.commit_actions(next_actions);
         }
 
-        let mut cross_swaps = Vec::new();
-
-        // Commit new actions
-        for (coord, actions) in next_actions {
+        for (coord, idx) in self.moved_tiles.drain(..) {
             if let Some(chunk) = self.alive_chunks.get_mut(&coord) {
-                for action in actions {
-                    match action {
-                        Action::Replace(idx, new_kind) => {
-                            chunk.tiles[idx] = new_kind;
-                            chunk.mark_dirty();
-                        }
-                        Action::Destroy(idx) => {
-                            chunk.tiles[idx] = TileKind::Empty;
-                            chunk.mark_dirty();
-                        }
-                        Action::Swap(idx_a, idx_b) => {
-                            chunk.tiles.swap(idx_a, idx_b);
-                            chunk.mark_dirty();
-                        }
-                        Action::SwapCrossChunk(idx_a, neighbor_coord, idx_b, tile_kind) => {
-                            // push into neighbor (deferred)
-                            cross_swaps.push((coord, idx_a, neighbor_coord, idx_b, tile_kind));
-                        }
-                        Action::None => {
-                            chunk.mark_clean();
+                chunk.tiles[idx].moved = false;
+            }
+        }
+
+        self.diffuse_heat(&dirty_coords);
+
+        // Count down gases and anything else that expires
+        for coord in &dirty_coords {
+            if let Some(chunk) = self.alive_chunks.get_mut(coord) {
+                chunk.age_tiles();
+            }
+        }
+    }
+
+    // Applies the actions of one colour phase. Every action was computed from
+    // the same state, so two of them may want the same tile, e.g. two grains
+    // falling into one gap. Actions are taken in chunk order and then scan
+    // order, and each claims the tiles it writes to; one that needs a tile
+    // already claimed is dropped and its tiles simply try again next step. No
+    // tile is written twice, so moves can never overwrite or duplicate material.
+    // Tiles that cross into another chunk are flagged until the end of the
+    // step, so one landing in a chunk of a later colour isn't moved a second
+    // time. A flagged tile that gets swapped out again lands in a chunk that
+    // has already had its turn, so apply_action clears its flag.
+    fn commit_actions(&mut self, next_actions: HashMap<ChunkCoord, Vec<Action>>) {
+        let mut coords: Vec<ChunkCoord> = next_actions.keys().copied().collect();
+        coords.sort_unstable();
+
+        let mut claimed: HashSet<(ChunkCoord, usize)> = HashSet::new();
+        for coord in coords {
+            for action in &next_actions[&coord] {
+                let targets = action.targets(coord);
+                let available = targets.iter().flatten().all(|&(target_coord, idx)| {
+                    !claimed.contains(&(target_coord, idx))
+                        && self.alive_chunks.contains_key(&target_coord)
+                });
+                if !available {
+                    for &(target_coord, idx) in targets.iter().flatten() {
+                        if let Some(chunk) = self.alive_chunks.get_mut(&target_coord) {
+                            chunk.mark_tile_dirty(idx % chunk.width, idx / chunk.width);
                         }
                     }
+                    continue;
                 }
+                claimed.extend(targets.iter().flatten());
+                self.apply_action(coord, action);
+                if let Some((target_coord, idx)) = action.moved_to()
+                    && let Some(chunk) = self.alive_chunks.get_mut(&target_coord)
+                {
+                    chunk.tiles[idx].moved = true;
+                    self.moved_tiles.push((target_coord, idx));
+                }
+                for &(target_coord, idx) in targets.iter().flatten() {
+                    self.mark_changed(target_coord, idx);
+                }
+            }
+        }
+    }
+
+    // Writes the tiles of one action; commit_actions marks them for next step
+    fn apply_action(&mut self, coord: ChunkCoord, action: &Action) {
+        // Write the half of a cross-chunk action that lands in the neighbour first
+        let mut swapped_out = None;
+        match *action {
+            Action::SwapCrossChunk(_, neighbor_coord, idx, tile) => {
+                let neighbor_chunk = self.alive_chunks.get_mut(&neighbor_coord).unwrap();
+                swapped_out = Some(std::mem::replace(&mut neighbor_chunk.tiles[idx], tile));
             }
+            Action::ReplaceCrossChunk(neighbor_coord, idx, tile)
+            | Action::ReactCrossChunk(_, _, neighbor_coord, idx, tile) => {
+                let neighbor_chunk = self.alive_chunks.get_mut(&neighbor_coord).unwrap();
+                neighbor_chunk.tiles[idx] = tile;
+            }
+            _ => {}
         }
 
-        for (coord, idx_a, neighbor_coord, idx_b, tile_kind) in cross_swaps {
-            // careful: fetch chunks separately
-            let other_tile =
-                if let Some(neighbor_chunk) = self.alive_chunks.get_mut(&neighbor_coord) {
-                    let other_tile = neighbor_chunk.tiles[idx_b];
-                    neighbor_chunk.tiles[idx_b] = tile_kind;
-                    neighbor_chunk.mark_dirty();
-                    other_tile
-                } else {
-                    TileKind::Empty
+        let chunk = self.alive_chunks.get_mut(&coord).unwrap();
+        match *action {
+            Action::Replace(idx, new_tile) => {
+                chunk.tiles[idx] = new_tile;
+            }
+            Action::Destroy(idx) => {
+                chunk.set_tile(idx, TileKind::EMPTY);
+            }
+            Action::Swap(idx_a, idx_b) => {
+                chunk.swap_tiles(idx_a, idx_b);
+                chunk.tiles[idx_a].moved = false;
+            }
+            Action::Move(idx_a, idx_b, velocity) => {
+                chunk.swap_tiles(idx_a, idx_b);
+                chunk.tiles[idx_a].moved = false;
+                chunk.tiles[idx_b].velocity = velocity;
+            }
+            Action::SetVelocity(idx, velocity) => {
+                chunk.tiles[idx].velocity = velocity;
+            }
+            Action::SwapCrossChunk(idx, ..) => {
+                chunk.tiles[idx] = Tile {
+                    moved: false,
+                    ..swapped_out.unwrap()
                 };
+            }
+            Action::React(idx_a, tile_a, idx_b, tile_b) => {
+                chunk.tiles[idx_a] = tile_a;
+                chunk.tiles[idx_b] = tile_b;
+            }
+            Action::ReactCrossChunk(idx, tile, ..) => {
+                chunk.tiles[idx] = tile;
+            }
+            Action::ReplaceCrossChunk(..) | Action::None => {}
+        }
+    }
+
+    // Marks a changed tile and the tiles around it, which may now be free to
+    // move, for the next step. Tiles across a chunk border wake their chunk.
+    fn mark_changed(&mut self, (chunk_x, chunk_y): ChunkCoord, idx: usize) {
+        let width = self.chunk_width as i32;
+        let height = self.chunk_height as i32;
+        let x = idx as i32 % width;
+        let y = idx as i32 / width;
+        if let Some(chunk) = self.alive_chunks.get_mut(&(chunk_x, chunk_y)) {
+            chunk.mark_tile_dirty(x as usize, y as usize);
+        }
 
+        let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+        if !on_border {
+            return;
+        }
+        for (offset_x, offset_y) in NEIGHBOR_OFFSETS {
+            let (next_x, next_y) = (x + offset_x, y + offset_y);
+            let coord = (
+                chunk_x + next_x.div_euclid(width),
+                chunk_y + next_y.div_euclid(height),
+            );
+            if coord == (chunk_x, chunk_y) {
+                continue;
+            }
             if let Some(chunk) = self.alive_chunks.get_mut(&coord) {
-                chunk.tiles[idx_a] = other_tile;
-                chunk.mark_dirty();
+                chunk.mark_tile_dirty(
+                    next_x.rem_euclid(width) as usize,
+                    next_y.rem_euclid(height) as usize,
+                );
+            }
+        }
+    }
+
+    fn diffuse_heat(&mut self, coords: &[ChunkCoord]) {
+        // Every chunk reads the old temperatures of its neighbours, so all of
+        // them are computed before any are written back
+        let diffusing: HashSet<ChunkCoord> = coords.iter().copied().collect();
+        let temperatures: Vec<_> = coords
+            .par_iter()
+            .filter_map(|coord| {
+                let neighbors = self.get_neighbors(*coord);
+                self.alive_chunks
+                    .get(coord)
+                    .map(|chunk| (*coord, chunk.diffuse_heat(&neighbors, &diffusing)))
+            })
+            .collect();
+
+        for (coord, (temperatures, warmed)) in temperatures {
+            let Some(chunk) = self.alive_chunks.get_mut(&coord) else {
+                continue;
+            };
+            // A tile that melted or froze may free up the tiles next to it
+            for idx in chunk.apply_temperatures(&temperatures) {
+                self.mark_changed(coord, idx);
+            }
+            for (neighbor_coord, idx) in warmed {
+                if let Some(neighbor) = self.alive_chunks.get_mut(&neighbor_coord) {
+                    neighbor.mark_tile_dirty(idx % neighbor.width, idx / neighbor.width);
+                }
             }
         }
     }
@@ -157,17 +355,61 @@ impl ChunkList {
         self.alive_chunks.get(coord)
     }
 
+    pub fn tile_at(&self, world_x: isize, world_y: isize) -> Option<&Tile> {
+        let chunk_x = div_floor(world_x, self.chunk_width as isize) as i32;
+        let chunk_y = div_floor(world_y, self.chunk_height as isize) as i32;
+        let local_x = euclidean_mod(world_x, self.chunk_width);
+        let local_y = euclidean_mod(world_y, self.chunk_height);
+        self.alive_chunks
+            .get(&(chunk_x, chunk_y))
+            .map(|chunk| &chunk.tiles[local_y * chunk.width + local_x])
+    }
+
+    // Places a fresh tile from outside the simulation, like the brush does,
+    // waking whatever it touches
+    pub fn set_tile(&mut self, world_x: isize, world_y: isize, kind: TileKind) {
+        let coord = (
+            div_floor(world_x, self.chunk_width as isize) as i32,
+            div_floor(world_y, self.chunk_height as isize) as i32,
+        );
+        let local_x = euclidean_mod(world_x, self.chunk_width);
+        let local_y = euclidean_mod(world_y, self.chunk_height);
+        let Some(chunk) = self.alive_chunks.get_mut(&coord) else {
+            return;
+        };
+        let idx = local_y * chunk.width + local_x;
+        chunk.set_tile(idx, kind);
+        self.mark_changed(coord, idx);
+    }
+
+    pub fn awake_chunks(&self) -> usize {
+        self.alive_chunks.values().filter(|chunk| chunk.is_dirty()).count()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&ChunkCoord, &Chunk)> {
         self.alive_chunks.iter()
     }
 
+    // Lowest and highest coordinates of the loaded chunks, None if there are none
+    pub fn bounds(&self) -> Option<(ChunkCoord, ChunkCoord)> {
+        self.alive_chunks.keys().fold(None, |bounds, &(x, y)| match bounds {
+            None => Some(((x, y), (x, y))),
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+            }
+        })
+    }
+
     pub fn get_or_create_chunk(&mut self, x: i32, y: i32) -> &mut Chunk {
         let coord = (x, y);
 
-        // Entry API lets us insert if missing and get mutable reference
-        self.alive_chunks
-            .entry(coord)
-            .or_insert_with(|| Chunk::new(self.chunk_width, self.chunk_height, x, y, true))
+        // Entry API lets us insert if missing and get mutable reference.
+        self.alive_chunks.entry(coord).or_insert_with(|| {
+            let tiles = self
+                .generator
+                .generate(coord, self.chunk_width, self.chunk_height);
+            Chunk::new(self.chunk_width, self.chunk_height, x, y, tiles)
+        })
     }
 
     pub fn extend_chunks(&mut self, dirty_chunks: &[ChunkCoord]) {
@@ -276,3 +518,246 @@ impl ChunkList {
         (cx, cy)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gravity::Gravity, world_gen::GeneratorKind};
+
+    const CHUNK_SIZE: usize = 16;
+    const WORLD_SIZE: i32 = 48; // a stone box filling 3x3 chunks
+
+    fn place(chunks: &mut ChunkList, x: i32, y: i32, kind: TileKind) {
+        let size = CHUNK_SIZE as i32;
+        chunks.get_or_create_chunk(x.div_euclid(size), y.div_euclid(size));
+        chunks.set_tile(x as isize, y as isize, kind);
+    }
+
+    // Empty tiles are left out, chunks around the box come and go
+    fn count_tiles(chunks: &ChunkList) -> HashMap<TileKind, usize> {
+        let mut counts = HashMap::new();
+        for (_, chunk) in chunks.iter() {
+            for tile in chunk.tiles.iter().filter(|tile| tile.kind != TileKind::EMPTY) {
+                *counts.entry(tile.kind).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    // Sand, water and oil in a closed box, all of it starting in motion across
+    // chunk borders
+    fn filled_box(seed: u64) -> ChunkList {
+        let mut chunks = ChunkList::new(
+            CHUNK_SIZE,
+            CHUNK_SIZE,
+            3,
+            3,
+            GeneratorKind::Empty.create(seed),
+            seed,
+        );
+        for i in 0..WORLD_SIZE {
+            for edge in [0, WORLD_SIZE - 1] {
+                place(&mut chunks, i, edge, TileKind::STONE);
+                place(&mut chunks, edge, i, TileKind::STONE);
+            }
+        }
+        let oil = crate::materials::registry()
+            .iter()
+            .find(|(_, material)| material.name == "Oil")
+            .map(|(kind, _)| kind)
+            .unwrap();
+        for y in 1..30 {
+            for x in 1..WORLD_SIZE - 1 {
+                let kind = match (x + y) % 5 {
+                    0 | 1 => TileKind::SAND,
+                    2 => TileKind::WATER,
+                    3 => oil,
+                    _ => continue,
+                };
+                place(&mut chunks, x, y, kind);
+            }
+        }
+        chunks
+    }
+
+    fn run(chunks: &mut ChunkList, steps: usize) {
+        for _ in 0..steps {
+            chunks.update();
+            // Keep the world from growing past the box
+            chunks.cull_chunks(0, 0, WORLD_SIZE as usize, WORLD_SIZE as usize, 0);
+        }
+    }
+
+    // Tiles still flagged as moved, which are skipped until the flag clears
+    fn flagged_tiles(chunks: &ChunkList) -> usize {
+        chunks
+            .iter()
+            .flat_map(|(_, chunk)| chunk.tiles.iter())
+            .filter(|tile| tile.moved)
+            .count()
+    }
+
+    #[test]
+    fn falling_conserves_every_material() {
+        let mut chunks = filled_box(0);
+        let before = count_tiles(&chunks);
+        run(&mut chunks, 2000);
+        assert_eq!(count_tiles(&chunks), before);
+        assert_eq!(flagged_tiles(&chunks), 0);
+    }
+
+    // Every tile of every chunk, in a fixed order
+    fn snapshot(chunks: &ChunkList) -> Vec<(ChunkCoord, Vec<Tile>)> {
+        let mut tiles: Vec<_> = chunks
+            .iter()
+            .map(|(coord, chunk)| (*coord, chunk.tiles.clone()))
+            .collect();
+        tiles.sort_by_key(|(coord, _)| *coord);
+        tiles
+    }
+
+    // The box from `filled_box` with a burning coal seam on the floor, so
+    // falling, splashing, fire, smoke and heat all draw random numbers
+    fn burning_box(seed: u64) -> ChunkList {
+        let mut chunks = filled_box(seed);
+        for x in 1..WORLD_SIZE - 1 {
+            place(&mut chunks, x, WORLD_SIZE - 2, TileKind::COAL);
+        }
+        for x in (4..WORLD_SIZE - 4).step_by(8) {
+            place(&mut chunks, x, WORLD_SIZE - 3, TileKind::FIRE);
+        }
+        chunks
+    }
+
+    #[test]
+    fn same_seed_replays_the_same_world() {
+        let mut first = burning_box(7);
+        let mut second = burning_box(7);
+        run(&mut first, 300);
+        run(&mut second, 300);
+        assert!(snapshot(&first) == snapshot(&second));
+
+        let mut other_seed = burning_box(8);
+        run(&mut other_seed, 300);
+        assert!(snapshot(&first) != snapshot(&other_seed));
+    }
+
+    #[test]
+    fn changing_gravity_conserves_every_material() {
+        let mut chunks = filled_box(0);
+        let before = count_tiles(&chunks);
+        for direction in [(1, 0), (-1, -1), (0, -1), (1, 1), (0, 0)] {
+            chunks.gravity = Gravity {
+                direction,
+                ..Gravity::default()
+            };
+            run(&mut chunks, 400);
+            assert_eq!(count_tiles(&chunks), before, "gravity {direction:?}");
+        }
+    }
+
+    #[test]
+    fn settled_chunks_sleep_until_their_support_is_removed() {
+        let mut chunks = ChunkList::new(
+            CHUNK_SIZE,
+            CHUNK_SIZE,
+            3,
+            3,
+            GeneratorKind::Empty.create(0),
+            0,
+        );
+        // A shelf along the top row of the middle chunks with sand on it, so
+        // the sand sits in the chunks above
+        for x in 0..WORLD_SIZE {
+            place(&mut chunks, x, 16, TileKind::STONE);
+        }
+        for y in 8..16 {
+            for x in 20..28 {
+                place(&mut chunks, x, y, TileKind::SAND);
+            }
+        }
+        run(&mut chunks, 300);
+        assert_eq!(chunks.awake_chunks(), 0);
+
+        chunks.set_tile(24, 16, TileKind::EMPTY);
+        assert!(chunks.get(&(1, 0)).unwrap().is_dirty());
+        run(&mut chunks, 10);
+        let fell_through = (17..WORLD_SIZE as isize)
+            .any(|y| chunks.tile_at(24, y).unwrap().kind == TileKind::SAND);
+        assert!(fell_through);
+    }
+
+    #[test]
+    fn tiles_crossing_into_a_later_colour_move_once_per_step() {
+        let mut chunks = ChunkList::new(
+            CHUNK_SIZE,
+            CHUNK_SIZE,
+            2,
+            2,
+            GeneratorKind::Empty.create(0),
+            0,
+        );
+        // On the bottom row of chunk (0, 0), which runs before chunk (0, 1)
+        place(&mut chunks, 5, 15, TileKind::SAND);
+        chunks.update();
+
+        assert_eq!(chunks.tile_at(5, 16).unwrap().kind, TileKind::SAND);
+        assert!(!chunks.tile_at(5, 16).unwrap().moved);
+        assert_eq!(count_tiles(&chunks).get(&TileKind::SAND), Some(&1));
+    }
+
+    #[test]
+    fn tiles_displaced_by_a_later_colour_keep_updating() {
+        let mut chunks = ChunkList::new(
+            CHUNK_SIZE,
+            CHUNK_SIZE,
+            2,
+            2,
+            GeneratorKind::Empty.create(0),
+            0,
+        );
+        // The water crosses into chunk (1, 0), then the sand there swaps it
+        // back out of the spot it was flagged at
+        place(&mut chunks, 15, 15, TileKind::WATER);
+        place(&mut chunks, 16, 15, TileKind::SAND);
+        place(&mut chunks, 16, 16, TileKind::STONE);
+        place(&mut chunks, 17, 16, TileKind::STONE);
+        chunks.update();
+
+        assert_eq!(flagged_tiles(&chunks), 0);
+        let water_at = |chunks: &ChunkList| {
+            (0..32)
+                .flat_map(|y| (0..32).map(move |x| (x, y)))
+                .find(|&(x, y)| chunks.tile_at(x, y).unwrap().kind == TileKind::WATER)
+        };
+        let before = water_at(&chunks);
+        run(&mut chunks, 50);
+        assert_ne!(water_at(&chunks), before);
+    }
+
+    #[test]
+    fn conflicting_moves_into_one_tile_keep_both_tiles() {
+        let mut chunks = ChunkList::new(
+            CHUNK_SIZE,
+            CHUNK_SIZE,
+            2,
+            2,
+            GeneratorKind::Empty.create(0),
+            0,
+        );
+        place(&mut chunks, 16, 16, TileKind::SAND);
+        place(&mut chunks, 15, 16, TileKind::SAND);
+        let sand = Tile::new(TileKind::SAND);
+
+        // Both grains want the empty tile at (15, 15), one from the chunk to the
+        // right and one from the chunk below
+        let target = ((0, 0), 15 * CHUNK_SIZE + 15);
+        let mut actions = HashMap::new();
+        actions.insert((1, 1), vec![Action::SwapCrossChunk(0, target.0, target.1, sand)]);
+        actions.insert((0, 1), vec![Action::SwapCrossChunk(15, target.0, target.1, sand)]);
+        chunks.commit_actions(actions);
+
+        assert_eq!(count_tiles(&chunks).get(&TileKind::SAND), Some(&2));
+        assert_eq!(chunks.tile_at(15, 15).unwrap().kind, TileKind::SAND);
+    }
+}
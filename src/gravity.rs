@@ -0,0 +1,78 @@
+// The eight neighbouring directions in clockwise order (positive y is down),
+// so that turning by an eighth is a step through the list
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+// World-wide pull on everything that falls, flows or rises. Rules look at
+// their surroundings relative to `direction`, so sideways gravity turns the
+// whole simulation on its side. A zero direction switches gravity off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gravity {
+    pub direction: (i32, i32), // one of the eight neighbour offsets, or (0, 0)
+    pub strength: f32,         // tiles per step added to the fall speed each step
+}
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self {
+            direction: (0, 1),
+            strength: 0.25,
+        }
+    }
+}
+
+impl Gravity {
+    pub const CHOICES: [(&'static str, (i32, i32)); 9] = [
+        ("Down", (0, 1)),
+        ("Down Left", (-1, 1)),
+        ("Left", (-1, 0)),
+        ("Up Left", (-1, -1)),
+        ("Up", (0, -1)),
+        ("Up Right", (1, -1)),
+        ("Right", (1, 0)),
+        ("Down Right", (1, 1)),
+        ("None", (0, 0)),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        Self::CHOICES
+            .iter()
+            .find(|(_, direction)| *direction == self.direction)
+            .map_or("None", |(name, _)| name)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.direction == (0, 0)
+    }
+
+    // Offset `eighths` turns clockwise from straight down, as seen from a tile
+    // standing upright in this gravity: 0 is below, 4 above, -2 to the right
+    // and 2 to the left. None without gravity, where nothing is below anything.
+    pub fn relative(&self, eighths: i32) -> Option<(i32, i32)> {
+        let down = DIRECTIONS.iter().position(|&d| d == self.direction)? as i32;
+        Some(DIRECTIONS[(down + eighths).rem_euclid(8) as usize])
+    }
+
+    // Unit vector of the pull, zero without gravity
+    pub fn unit(&self) -> (f32, f32) {
+        unit_vector(self.direction)
+    }
+}
+
+pub fn unit_vector((x, y): (i32, i32)) -> (f32, f32) {
+    let (x, y) = (x as f32, y as f32);
+    let length = (x * x + y * y).sqrt();
+    if length == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (x / length, y / length)
+    }
+}
@@ -0,0 +1,21 @@
+// The simulation and world generation, with no UI attached. The egui app in
+// main.rs and the headless runner in bin/headless.rs are both built on it.
+
+pub mod action;
+pub mod bresenham;
+pub mod chunk;
+pub mod chunk_list;
+pub mod gravity;
+pub mod materials;
+pub mod math;
+pub mod noise;
+pub mod ppm;
+pub mod reactions;
+pub mod rng;
+pub mod save;
+pub mod sim_clock;
+pub mod simulation;
+pub mod tile;
+pub mod tile_checks;
+pub mod tiles;
+pub mod world_gen;
@@ -1,25 +1,31 @@
-use crate::math::{div_floor, euclidean_mod};
 use eframe::egui;
 
-mod action;
-mod bresenham;
-mod chunk;
-mod chunk_list;
 mod colors;
-mod math;
-mod tile_checks;
-mod tile_map;
-mod tiles;
 mod viewport;
 
-use bresenham::plot_line;
-use chunk_list::ChunkList;
 use egui::{ComboBox, Pos2, Vec2};
-use std::time::Instant;
-use tiles::tile_kind::TileKind;
+use rust_procedural_world_generation::{
+    bresenham::plot_line,
+    chunk_list::ChunkList,
+    gravity::Gravity,
+    materials,
+    sim_clock::SimClock,
+    simulation::{SimCommand, Simulation, Snapshot},
+    tiles::tile_kind::TileKind,
+    world_gen::{GeneratorKind, WorldGenerator},
+};
+use std::{path::PathBuf, sync::Arc, time::Instant};
 use viewport::Viewport;
 
 fn main() -> eframe::Result<()> {
+    let startup = StartupOptions::from_args(std::env::args().skip(1));
+
+    // An explicit --materials file must exist
+    if let Err(error) = materials::init_from(startup.materials.as_deref()) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1920.0, 1080.0]), // width, height
         ..Default::default()
@@ -27,24 +33,64 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "My egui App",
         options,
-        Box::new(|_cc| Ok(Box::<MyApp>::default())),
+        Box::new(move |_cc| Ok(Box::new(MyApp::new(startup)))),
     )
 }
 
+struct StartupOptions {
+    generator: GeneratorKind,
+    seed: u64,
+    materials: Option<PathBuf>,
+}
+
+impl StartupOptions {
+    // Usage: [--generator empty|soup|terrain] [--seed <u64>] [--materials <file>]
+    fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = Self {
+            generator: GeneratorKind::Terrain,
+            seed: 1337,
+            materials: None,
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--generator" => match args.next().as_deref().and_then(GeneratorKind::from_name) {
+                    Some(kind) => options.generator = kind,
+                    None => eprintln!("expected one of empty|soup|terrain after --generator"),
+                },
+                "--seed" => match args.next().and_then(|s| s.parse().ok()) {
+                    Some(seed) => options.seed = seed,
+                    None => eprintln!("expected an unsigned integer after --seed"),
+                },
+                "--materials" => match args.next() {
+                    Some(path) => options.materials = Some(PathBuf::from(path)),
+                    None => eprintln!("expected a file path after --materials"),
+                },
+                other => eprintln!("ignoring unknown argument {other}"),
+            }
+        }
+
+        options
+    }
+}
+
 struct MyApp {
     viewport: Viewport,
-    chunks: ChunkList,
+    simulation: Simulation,
+    snapshot: Arc<Snapshot>, // what this frame shows
+    generator: Box<dyn WorldGenerator>, // same world as the simulation's, for biome lookups
     simulation_running: bool,
-    simulation_speed: u8,
-    current_frame: u64,
+    target_tps: f32,
+    gravity: Gravity,
     frame_timer: FrameTimer,
     brush_size: usize,
     brush_element: TileKind,
     last_mouse_pos: Option<Pos2>,
+    hovered_tile: Option<(isize, isize)>,
 }
 
-impl Default for MyApp {
-    fn default() -> Self {
+impl MyApp {
+    fn new(startup: StartupOptions) -> Self {
         let scale = 4;
         let viewport_width = 1280;
         let viewport_height = 720;
@@ -55,22 +101,37 @@ impl Default for MyApp {
         let chunk_y_num = 32;
         let buffer_chunks = 32;
 
-        let starting_speed = 100;
+        let starting_tps = 60.0;
         let initial_run_state = true;
 
         // Create viewport
         let viewport = Viewport::new(viewport_width, viewport_height, scale, buffer_chunks);
 
+        let chunks = ChunkList::new(
+            chunk_width,
+            chunk_height,
+            chunk_x_num,
+            chunk_y_num,
+            startup.generator.create(startup.seed),
+            startup.seed,
+        );
+        let gravity = chunks.gravity;
+        let simulation =
+            Simulation::spawn(chunks, viewport.region(), initial_run_state, starting_tps);
+
         Self {
+            snapshot: simulation.snapshot(),
             viewport,
-            chunks: ChunkList::new(chunk_width, chunk_height, chunk_x_num, chunk_y_num),
+            simulation,
+            generator: startup.generator.create(startup.seed),
             simulation_running: initial_run_state,
-            simulation_speed: starting_speed,
-            current_frame: 0,
+            target_tps: starting_tps,
+            gravity,
             frame_timer: FrameTimer::new(),
             brush_size: 3,
             last_mouse_pos: None,
-            brush_element: TileKind::GameOfLife,
+            hovered_tile: None,
+            brush_element: TileKind::GAME_OF_LIFE,
         }
     }
 }
@@ -79,7 +140,7 @@ impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.viewport.init_texture(ctx);
 
-        self.update_if_needed();
+        self.snapshot = self.simulation.snapshot();
 
         egui::SidePanel::left("side_panel")
             .exact_width(300.0)
@@ -102,61 +163,33 @@ impl eframe::App for MyApp {
 
         ctx.request_repaint();
 
-        self.current_frame = self.current_frame.wrapping_add(1);
         self.frame_timer.tick();
     }
 }
 
 impl MyApp {
-    fn should_update(&self) -> bool {
-        let speed = self.simulation_speed.clamp(0, 99);
-
-        // Compute the interval: higher speed = smaller interval
-        let interval = 101 - speed; // Maps 1-100 to 100-1
-        // Update every `interval` frames
-        let should_update = self.simulation_running && (self.current_frame % interval as u64 == 0);
-        should_update
-    }
-
     fn tile_kind_selector(&mut self, ui: &mut egui::Ui) {
         ComboBox::from_label("Tile Type")
-            .selected_text(format!("{:?}", self.brush_element))
+            .selected_text(self.brush_element.name())
             .show_ui(ui, |ui| {
-                ui.selectable_value(&mut self.brush_element, TileKind::Empty, "Empty");
-                ui.selectable_value(
-                    &mut self.brush_element,
-                    TileKind::GameOfLife,
-                    "Game of Life",
-                );
-                ui.selectable_value(&mut self.brush_element, TileKind::Sand, "Sand");
-                ui.selectable_value(&mut self.brush_element, TileKind::Stone, "Stone");
+                for (kind, material) in materials::registry().iter() {
+                    ui.selectable_value(&mut self.brush_element, kind, &material.name);
+                }
             });
     }
 
-    fn update_if_needed(&mut self) {
-        if self.should_update() {
-            self.chunks.revive_chunks_near_viewport(
-                self.viewport.offset_x as isize / self.viewport.scale as isize,
-                self.viewport.offset_y as isize / self.viewport.scale as isize,
-                self.viewport.width_tiles,
-                self.viewport.height_tiles,
-                self.viewport.buffer_chunks, // buffer chunks
-            );
-            self.chunks.cull_chunks(
-                self.viewport.offset_x as isize / self.viewport.scale as isize,
-                self.viewport.offset_y as isize / self.viewport.scale as isize,
-                self.viewport.width_tiles,
-                self.viewport.height_tiles,
-                self.viewport.buffer_chunks, // buffer chunks
-            );
-            self.chunks.update();
-        }
-    }
-
     fn create_left_control_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.heading("Controls");
 
-        ui.add(egui::Slider::new(&mut self.simulation_speed, 1..=100).text("Simulation Speed"));
+        let tps_slider = ui.add(
+            egui::Slider::new(&mut self.target_tps, SimClock::MIN_TPS..=SimClock::MAX_TPS)
+                .logarithmic(true)
+                .integer()
+                .text("Ticks per Second"),
+        );
+        if tps_slider.changed() {
+            self.simulation.send(SimCommand::SetTargetTps(self.target_tps));
+        }
         if ui
             .button(if self.simulation_running {
                 "Pause"
@@ -166,51 +199,90 @@ impl MyApp {
             .clicked()
         {
             self.simulation_running = !self.simulation_running;
+            self.simulation.send(SimCommand::SetRunning(self.simulation_running));
         }
 
         if ui.button("Reset Viewport").clicked() {
             self.viewport.offset_x = 0;
             self.viewport.offset_y = 0;
+            self.simulation.send(SimCommand::SetView(self.viewport.region()));
         }
         ui.add_enabled_ui(!self.simulation_running, |ui| {
             if ui.button("Step Simulation").clicked() {
-                self.chunks.update();
+                self.simulation.send(SimCommand::Step);
             }
         });
 
         ui.label(format!("FPS: {:.1}", self.frame_timer.get_fps()));
+        ui.label(format!(
+            "TPS: {:.1} / {:.0}",
+            self.snapshot.tps, self.target_tps
+        ));
+        ui.label(format!(
+            "Awake chunks: {} / {}",
+            self.snapshot.awake_chunks, self.snapshot.loaded_chunks
+        ));
+
+        ui.separator();
+        let gravity = &mut self.gravity;
+        let previous = *gravity;
+        ComboBox::from_label("Gravity")
+            .selected_text(gravity.name())
+            .show_ui(ui, |ui| {
+                for (name, direction) in Gravity::CHOICES {
+                    ui.selectable_value(&mut gravity.direction, direction, name);
+                }
+            });
+        ui.add_enabled(
+            !gravity.is_zero(),
+            egui::Slider::new(&mut gravity.strength, 0.0..=1.0).text("Gravity Strength"),
+        );
+        if *gravity != previous {
+            self.simulation.send(SimCommand::SetGravity(*gravity));
+        }
+        ui.separator();
+
+        let biome = self
+            .hovered_tile
+            .and_then(|(x, y)| self.generator.biome_at(x as i64, y as i64));
+        match biome {
+            Some(biome) => ui.label(format!("Biome: {:?}", biome)),
+            None => ui.label("Biome: -"),
+        };
+
+        let tile = self
+            .hovered_tile
+            .and_then(|(x, y)| self.snapshot.tile_at(x, y));
+        match tile {
+            Some(tile) => ui.label(format!("Temperature: {:.0}°C", tile.temperature)),
+            None => ui.label("Temperature: -"),
+        };
     }
 
     fn calculate_max_pixel_coord(&self, max_chunk_x: i32, max_chunk_y: i32) -> (i32, i32) {
         let max_pixels_x =
-            (max_chunk_x + 1) * self.chunks.chunk_width as i32 * self.viewport.scale as i32
+            (max_chunk_x + 1) * self.snapshot.chunk_width as i32 * self.viewport.scale as i32
                 - self.viewport.width_pixels as i32;
         let max_pixels_y =
-            (max_chunk_y + 1) * self.chunks.chunk_height as i32 * self.viewport.scale as i32
+            (max_chunk_y + 1) * self.snapshot.chunk_height as i32 * self.viewport.scale as i32
                 - self.viewport.height_pixels as i32;
         (max_pixels_x, max_pixels_y)
     }
 
     fn calculate_min_pixel_coord(&self, min_chunk_x: i32, min_chunk_y: i32) -> (i32, i32) {
         let min_pixels_x =
-            min_chunk_x * self.chunks.chunk_width as i32 * self.viewport.scale as i32;
+            min_chunk_x * self.snapshot.chunk_width as i32 * self.viewport.scale as i32;
         let min_pixels_y =
-            min_chunk_y * self.chunks.chunk_height as i32 * self.viewport.scale as i32;
+            min_chunk_y * self.snapshot.chunk_height as i32 * self.viewport.scale as i32;
         (min_pixels_x, min_pixels_y)
     }
 
     fn move_viewport(&mut self, delta: Vec2) {
-        let mut min_chunk_x = i32::MAX;
-        let mut min_chunk_y = i32::MAX;
-        let mut max_chunk_x = i32::MIN;
-        let mut max_chunk_y = i32::MIN;
-
-        for (&(chunk_x, chunk_y), _) in self.chunks.iter() {
-            min_chunk_x = min_chunk_x.min(chunk_x);
-            min_chunk_y = min_chunk_y.min(chunk_y);
-            max_chunk_x = max_chunk_x.max(chunk_x);
-            max_chunk_y = max_chunk_y.max(chunk_y);
-        }
+        let Some(((min_chunk_x, min_chunk_y), (max_chunk_x, max_chunk_y))) =
+            self.snapshot.chunk_bounds
+        else {
+            return;
+        };
 
         // Convert chunk coordinates to pixel bounds
         let (min_pixels_x, min_pixels_y) = self.calculate_min_pixel_coord(min_chunk_x, min_chunk_y);
@@ -222,6 +294,8 @@ impl MyApp {
 
         self.viewport.offset_y = ((self.viewport.offset_y - delta.y as isize) as i32)
             .clamp(min_pixels_y, max_pixels_y) as isize;
+
+        self.simulation.send(SimCommand::SetView(self.viewport.region()));
     }
 
     pub fn paint_line(&mut self, start: Pos2, end: Pos2, brush_size: usize) {
@@ -237,39 +311,19 @@ impl MyApp {
         let line_points = plot_line(start_tile_x, start_tile_y, end_tile_x, end_tile_y);
         let brush_radius = (brush_size / 2) as isize;
 
+        let mut tiles = Vec::new();
         for (tx, ty) in line_points {
             for dy in -brush_radius..=brush_radius {
                 for dx in -brush_radius..=brush_radius {
-                    let world_x = tx + dx;
-                    let world_y = ty + dy;
-
-                    let chunk_w = self.chunks.chunk_width;
-                    let chunk_h = self.chunks.chunk_height;
-
-                    // Use floor division to handle negative coordinates correctly
-                    let chunk_x = div_floor(world_x, chunk_w as isize);
-                    let chunk_y = div_floor(world_y, chunk_h as isize);
-                    let local_x = euclidean_mod(world_x, chunk_w);
-                    let local_y = euclidean_mod(world_y, chunk_h);
-
-                    if let Some(chunk) = self
-                        .chunks
-                        .alive_chunks
-                        .get_mut(&(chunk_x as i32, chunk_y as i32))
-                    {
-                        let idx = local_y as usize * chunk.width + local_x as usize;
-                        if idx < chunk.tiles.len() {
-                            chunk.tiles[idx] = self.brush_element;
-                            chunk.mark_dirty();
-                        }
-                    }
+                    tiles.push((tx + dx, ty + dy));
                 }
             }
         }
+        self.simulation.send(SimCommand::Paint(tiles, self.brush_element));
     }
 
     fn create_central_panel(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
-        self.viewport.set_texture_from_chunks(ui, &self.chunks);
+        self.viewport.set_texture_from_chunks(ui, &self.snapshot);
 
         // Allocate a region for the viewport that can receive drag input
         let (rect, response) = ui.allocate_exact_size(
@@ -280,6 +334,16 @@ impl MyApp {
             egui::Sense::click_and_drag(),
         );
 
+        // Remember which world tile is under the cursor for the side panel
+        self.hovered_tile = response.hover_pos().map(|pos| {
+            let local_pos = pos - rect.min;
+            let scale = self.viewport.scale as f32;
+            (
+                ((local_pos.x + self.viewport.offset_x as f32) / scale).floor() as isize,
+                ((local_pos.y + self.viewport.offset_y as f32) / scale).floor() as isize,
+            )
+        });
+
         // Handle right-click drag
         if response.dragged_by(egui::PointerButton::Secondary) {
             let delta = response.drag_delta();
@@ -0,0 +1,573 @@
+use crate::{reactions::Reaction, tile::AMBIENT_TEMPERATURE, tiles::tile_kind::TileKind};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::Path,
+    sync::OnceLock,
+};
+
+// Compiled in so the game still runs without a materials file next to it
+const DEFAULT_MATERIALS: &str = include_str!("../materials.toml");
+
+// Diffusion stops being stable above this (see Chunk::diffuse_heat)
+const MAX_CONDUCTIVITY: f32 = 0.2;
+
+static REGISTRY: OnceLock<MaterialRegistry> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Behaviour {
+    Static,
+    Powder,
+    Liquid,
+    Gas,
+    Fire,
+    Life,
+}
+
+// A phase change once the temperature leaves a range
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    pub into: TileKind,
+    pub below: Option<f32>,
+    pub above: Option<f32>,
+}
+
+pub struct Material {
+    pub name: String,
+    pub color: [u8; 4],
+    pub behaviour: Behaviour,
+    pub density: u8,
+    pub dispersion_rate: usize,
+    pub lifetime: Option<u16>,
+    pub flammability: f32,
+    pub burn_time: u16,
+    pub temperature: f32,
+    pub hold_temperature: bool,
+    pub conductivity: f32,
+    pub heat_capacity: f32,
+    pub transitions: Vec<Transition>,
+    pub reactions: Vec<Reaction>, // reactions with this material as the reactant
+}
+
+// Every material the simulation knows about, indexed by TileKind
+pub struct MaterialRegistry {
+    materials: Vec<Material>,
+}
+
+#[derive(Debug)]
+pub enum MaterialError {
+    Io(String, std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(Vec<String>), // one message per problem found
+}
+
+impl fmt::Display for MaterialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaterialError::Io(path, error) => write!(f, "could not read {path}: {error}"),
+            MaterialError::Parse(error) => write!(f, "invalid materials file: {error}"),
+            MaterialError::Invalid(errors) => {
+                writeln!(f, "invalid material definitions:")?;
+                for error in errors {
+                    writeln!(f, "  - {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// File layout, before names are resolved and values checked
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialsFile {
+    #[serde(default)]
+    material: Vec<MaterialDef>,
+    #[serde(default)]
+    reaction: Vec<ReactionDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDef {
+    name: String,
+    color: [u8; 3],
+    behaviour: Behaviour,
+    density: Option<u8>,
+    #[serde(default)]
+    dispersion_rate: usize,
+    lifetime: Option<u16>,
+    #[serde(default)]
+    flammability: f32,
+    burn_time: Option<u16>,
+    #[serde(default = "ambient")]
+    temperature: f32,
+    #[serde(default)]
+    hold_temperature: bool,
+    #[serde(default = "default_conductivity")]
+    conductivity: f32,
+    #[serde(default = "default_heat_capacity")]
+    heat_capacity: f32,
+    #[serde(default)]
+    transition: Vec<TransitionDef>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransitionDef {
+    into: String,
+    below: Option<f32>,
+    above: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReactionDef {
+    reactant: String,
+    other: String,
+    product: String,
+    other_product: String,
+    chance: f32,
+    min_temperature: Option<f32>,
+}
+
+fn ambient() -> f32 {
+    AMBIENT_TEMPERATURE
+}
+
+fn default_conductivity() -> f32 {
+    0.05
+}
+
+fn default_heat_capacity() -> f32 {
+    1.0
+}
+
+impl MaterialRegistry {
+    pub fn load(path: &Path) -> Result<Self, MaterialError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|error| MaterialError::Io(path.display().to_string(), error))?;
+        Self::from_toml(&source)
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, MaterialError> {
+        let file: MaterialsFile = toml::from_str(source).map_err(MaterialError::Parse)?;
+        let mut errors = Vec::new();
+
+        let mut seen = HashSet::new();
+        for def in &file.material {
+            if !seen.insert(def.name.as_str()) {
+                errors.push(format!("material '{}' is defined more than once", def.name));
+            }
+        }
+
+        // Built-in materials take the ids TileKind's constants expect, the rest
+        // follow in file order
+        let mut order: Vec<&MaterialDef> = Vec::with_capacity(file.material.len());
+        for (name, behaviour) in TileKind::BUILT_IN {
+            match file.material.iter().find(|def| def.name == name) {
+                Some(def) => {
+                    if def.behaviour != behaviour {
+                        errors.push(format!(
+                            "built-in material '{name}' must have behaviour \"{}\"",
+                            format!("{behaviour:?}").to_lowercase()
+                        ));
+                    }
+                    order.push(def);
+                }
+                None => errors.push(format!("built-in material '{name}' is missing")),
+            }
+        }
+        order.extend(
+            file.material
+                .iter()
+                .filter(|def| TileKind::BUILT_IN.iter().all(|(name, _)| def.name != *name)),
+        );
+
+        let ids: HashMap<&str, TileKind> = order
+            .iter()
+            .enumerate()
+            .map(|(id, def)| (def.name.as_str(), TileKind(id as u16)))
+            .collect();
+        let lookup = |name: &str, context: &str, errors: &mut Vec<String>| match ids.get(name) {
+            Some(&kind) => kind,
+            None => {
+                errors.push(format!("{context} refers to unknown material '{name}'"));
+                TileKind::EMPTY
+            }
+        };
+
+        let fire_lifetime = file
+            .material
+            .iter()
+            .find(|def| def.name == "Fire")
+            .and_then(|def| def.lifetime)
+            .unwrap_or(0);
+
+        let mut materials = Vec::with_capacity(order.len());
+        for def in &order {
+            let context = format!("material '{}'", def.name);
+            validate(def, &context, &mut errors);
+
+            let transitions = def
+                .transition
+                .iter()
+                .map(|transition| Transition {
+                    into: lookup(&transition.into, &context, &mut errors),
+                    below: transition.below,
+                    above: transition.above,
+                })
+                .collect();
+
+            materials.push(Material {
+                name: def.name.clone(),
+                color: [def.color[0], def.color[1], def.color[2], 255],
+                behaviour: def.behaviour,
+                density: def.density.unwrap_or(if def.behaviour == Behaviour::Gas {
+                    0
+                } else {
+                    u8::MAX
+                }),
+                dispersion_rate: def.dispersion_rate,
+                lifetime: def.lifetime,
+                flammability: def.flammability,
+                burn_time: def.burn_time.unwrap_or(fire_lifetime),
+                temperature: def.temperature,
+                hold_temperature: def.hold_temperature,
+                conductivity: def.conductivity,
+                heat_capacity: def.heat_capacity,
+                transitions,
+                reactions: Vec::new(),
+            });
+        }
+
+        for (i, def) in file.reaction.iter().enumerate() {
+            let context = format!("reaction {} ({} + {})", i + 1, def.reactant, def.other);
+            if !(0.0..=1.0).contains(&def.chance) {
+                errors.push(format!("{context}: chance {} is not between 0 and 1", def.chance));
+            }
+            if let Some(min_temperature) = def.min_temperature
+                && !min_temperature.is_finite()
+            {
+                errors.push(format!(
+                    "{context}: min_temperature {min_temperature} must be finite"
+                ));
+            }
+            let reactant = lookup(&def.reactant, &context, &mut errors);
+            let reaction = Reaction {
+                other: lookup(&def.other, &context, &mut errors),
+                product: lookup(&def.product, &context, &mut errors),
+                other_product: lookup(&def.other_product, &context, &mut errors),
+                chance: def.chance,
+                min_temperature: def.min_temperature,
+            };
+            if let Some(material) = materials.get_mut(reactant.0 as usize) {
+                material.reactions.push(reaction);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Self { materials })
+        } else {
+            Err(MaterialError::Invalid(errors))
+        }
+    }
+
+    pub fn get(&self, kind: TileKind) -> &Material {
+        &self.materials[kind.0 as usize]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (TileKind, &Material)> {
+        self.materials
+            .iter()
+            .enumerate()
+            .map(|(id, material)| (TileKind(id as u16), material))
+    }
+}
+
+fn validate(def: &MaterialDef, context: &str, errors: &mut Vec<String>) {
+    if def.name.is_empty() {
+        errors.push("a material has an empty name".to_string());
+    }
+    if !(0.0..=MAX_CONDUCTIVITY).contains(&def.conductivity) {
+        errors.push(format!(
+            "{context}: conductivity {} must be between 0 and {MAX_CONDUCTIVITY}",
+            def.conductivity
+        ));
+    }
+    // Checked for NaN too, which would spread to every neighbour through diffusion
+    if !def.heat_capacity.is_finite() || def.heat_capacity < 1.0 {
+        errors.push(format!(
+            "{context}: heat_capacity {} must be at least 1",
+            def.heat_capacity
+        ));
+    }
+    if !def.temperature.is_finite() {
+        errors.push(format!("{context}: temperature {} must be finite", def.temperature));
+    }
+    if !(0.0..=1.0).contains(&def.flammability) {
+        errors.push(format!(
+            "{context}: flammability {} is not between 0 and 1",
+            def.flammability
+        ));
+    }
+    if def.dispersion_rate > 0 && def.behaviour != Behaviour::Liquid {
+        errors.push(format!("{context}: dispersion_rate only applies to liquids"));
+    }
+    match (def.behaviour, def.lifetime) {
+        (Behaviour::Gas | Behaviour::Fire, None) => {
+            errors.push(format!("{context}: gas and fire materials need a lifetime"))
+        }
+        (Behaviour::Gas | Behaviour::Fire, Some(_)) | (_, None) => {}
+        (_, Some(_)) => errors.push(format!("{context}: only gas and fire materials expire")),
+    }
+    for transition in &def.transition {
+        if transition.below.is_some() == transition.above.is_some() {
+            errors.push(format!(
+                "{context}: transition into '{}' needs exactly one of below or above",
+                transition.into
+            ));
+        }
+        if let Some(threshold) = transition.below.or(transition.above)
+            && !threshold.is_finite()
+        {
+            errors.push(format!(
+                "{context}: transition into '{}' at {threshold} must be finite",
+                transition.into
+            ));
+        }
+    }
+}
+
+// Installs the materials used for the rest of the run. Only the first call
+// has any effect.
+pub fn init(registry: MaterialRegistry) {
+    let _ = REGISTRY.set(registry);
+}
+
+// Installs the materials in `path`. Without one, materials.toml is read from
+// the working directory if it exists, and the built-in copy is used if not.
+pub fn init_from(path: Option<&Path>) -> Result<(), MaterialError> {
+    let default = Path::new("materials.toml");
+    if let Some(path) = path.or_else(|| default.exists().then_some(default)) {
+        init(MaterialRegistry::load(path)?);
+    }
+    Ok(())
+}
+
+// Falls back to the built-in definitions if init was never called
+pub fn registry() -> &'static MaterialRegistry {
+    REGISTRY.get_or_init(|| {
+        MaterialRegistry::from_toml(DEFAULT_MATERIALS).expect("built-in materials.toml is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The smallest valid file: just the built-in materials
+    fn built_ins() -> String {
+        let mut source = String::new();
+        for (name, behaviour) in TileKind::BUILT_IN {
+            let behaviour = format!("{behaviour:?}").to_lowercase();
+            source += &format!(
+                "[[material]]\nname = \"{name}\"\ncolor = [0, 0, 0]\nbehaviour = \"{behaviour}\"\n"
+            );
+            if matches!(name, "Smoke" | "Fire") {
+                source += "lifetime = 10\n";
+            }
+        }
+        source
+    }
+
+    // Errors from loading the built-ins followed by `extra`
+    fn errors_with(extra: &str) -> Vec<String> {
+        match MaterialRegistry::from_toml(&(built_ins() + extra)) {
+            Err(MaterialError::Invalid(errors)) => errors,
+            Err(error) => panic!("expected invalid definitions, got {error}"),
+            Ok(_) => panic!("expected invalid definitions, but the file loaded"),
+        }
+    }
+
+    fn assert_error(extra: &str, expected: &str) {
+        let errors = errors_with(extra);
+        assert!(
+            errors.iter().any(|error| error.contains(expected)),
+            "no error containing {expected:?} in {errors:?}"
+        );
+    }
+
+    #[test]
+    fn bundled_materials_file_is_valid() {
+        if let Err(error) = MaterialRegistry::from_toml(DEFAULT_MATERIALS) {
+            panic!("{error}");
+        }
+    }
+
+    #[test]
+    fn built_in_materials_get_the_ids_tile_kind_expects() {
+        // Listed backwards and after a custom material, so file order can't help
+        let mut source = String::from(
+            "[[material]]\nname = \"Custom\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n",
+        );
+        let built_ins = built_ins();
+        let mut definitions: Vec<_> = built_ins.split("[[material]]").skip(1).collect();
+        definitions.reverse();
+        for definition in definitions {
+            source += "[[material]]";
+            source += definition;
+        }
+
+        for registry in [
+            MaterialRegistry::from_toml(&source).unwrap(),
+            MaterialRegistry::from_toml(DEFAULT_MATERIALS).unwrap(),
+        ] {
+            let constants = [
+                (TileKind::EMPTY, "Empty"),
+                (TileKind::GAME_OF_LIFE, "GameOfLife"),
+                (TileKind::SAND, "Sand"),
+                (TileKind::STONE, "Stone"),
+                (TileKind::COAL, "Coal"),
+                (TileKind::IRON, "Iron"),
+                (TileKind::GOLD, "Gold"),
+                (TileKind::WATER, "Water"),
+                (TileKind::SMOKE, "Smoke"),
+                (TileKind::FIRE, "Fire"),
+            ];
+            for (kind, name) in constants {
+                assert_eq!(registry.get(kind).name, name);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        assert_error(
+            "[[material]]\nname = \"Sand\"\ncolor = [1, 1, 1]\nbehaviour = \"powder\"\n",
+            "'Sand' is defined more than once",
+        );
+    }
+
+    #[test]
+    fn missing_built_in_materials_are_rejected() {
+        let source = built_ins().replace("name = \"Coal\"", "name = \"Charcoal\"");
+        match MaterialRegistry::from_toml(&source) {
+            Err(MaterialError::Invalid(errors)) => {
+                assert!(errors.iter().any(|error| error.contains("'Coal' is missing")))
+            }
+            _ => panic!("a file without Coal loaded"),
+        }
+    }
+
+    #[test]
+    fn built_in_materials_must_keep_their_behaviour() {
+        let source = built_ins().replace(
+            "name = \"Water\"\ncolor = [0, 0, 0]\nbehaviour = \"liquid\"",
+            "name = \"Water\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"",
+        );
+        match MaterialRegistry::from_toml(&source) {
+            Err(MaterialError::Invalid(errors)) => assert!(
+                errors
+                    .iter()
+                    .any(|error| error.contains("'Water' must have behaviour \"liquid\""))
+            ),
+            _ => panic!("static water loaded"),
+        }
+    }
+
+    #[test]
+    fn unknown_names_in_transitions_and_reactions_are_rejected() {
+        assert_error(
+            "[[material]]\nname = \"Mud\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             [[material.transition]]\ninto = \"Brick\"\nabove = 500.0\n",
+            "material 'Mud' refers to unknown material 'Brick'",
+        );
+        assert_error(
+            "[[reaction]]\nreactant = \"Sand\"\nother = \"Slime\"\nproduct = \"Empty\"\n\
+             other_product = \"Empty\"\nchance = 0.5\n",
+            "refers to unknown material 'Slime'",
+        );
+    }
+
+    #[test]
+    fn transitions_need_exactly_one_threshold() {
+        for thresholds in ["below = 0.0\nabove = 100.0\n", ""] {
+            assert_error(
+                &format!(
+                    "[[material]]\nname = \"Mud\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+                     [[material.transition]]\ninto = \"Stone\"\n{thresholds}"
+                ),
+                "needs exactly one of below or above",
+            );
+        }
+    }
+
+    #[test]
+    fn dispersion_rate_only_applies_to_liquids() {
+        assert_error(
+            "[[material]]\nname = \"Dust\"\ncolor = [0, 0, 0]\nbehaviour = \"powder\"\n\
+             dispersion_rate = 3\n",
+            "dispersion_rate only applies to liquids",
+        );
+    }
+
+    #[test]
+    fn only_gas_and_fire_have_lifetimes() {
+        assert_error(
+            "[[material]]\nname = \"Dust\"\ncolor = [0, 0, 0]\nbehaviour = \"powder\"\n\
+             lifetime = 10\n",
+            "only gas and fire materials expire",
+        );
+        assert_error(
+            "[[material]]\nname = \"Fog\"\ncolor = [0, 0, 0]\nbehaviour = \"gas\"\n",
+            "gas and fire materials need a lifetime",
+        );
+    }
+
+    #[test]
+    fn out_of_range_numbers_are_rejected() {
+        assert_error(
+            "[[reaction]]\nreactant = \"Sand\"\nother = \"Water\"\nproduct = \"Empty\"\n\
+             other_product = \"Empty\"\nchance = 1.5\n",
+            "chance 1.5 is not between 0 and 1",
+        );
+        assert_error(
+            "[[material]]\nname = \"Copper\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             conductivity = 0.5\n",
+            "conductivity 0.5 must be between 0 and 0.2",
+        );
+        assert_error(
+            "[[material]]\nname = \"Paper\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             flammability = 2.0\n",
+            "flammability 2 is not between 0 and 1",
+        );
+        assert_error(
+            "[[material]]\nname = \"Foam\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             heat_capacity = 0.5\n",
+            "heat_capacity 0.5 must be at least 1",
+        );
+        assert_error(
+            "[[material]]\nname = \"Foam\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             heat_capacity = nan\n",
+            "heat_capacity NaN must be at least 1",
+        );
+        assert_error(
+            "[[material]]\nname = \"Foam\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             temperature = inf\n",
+            "temperature inf must be finite",
+        );
+        assert_error(
+            "[[material]]\nname = \"Mud\"\ncolor = [0, 0, 0]\nbehaviour = \"static\"\n\
+             [[material.transition]]\ninto = \"Stone\"\nabove = nan\n",
+            "transition into 'Stone' at NaN must be finite",
+        );
+        assert_error(
+            "[[reaction]]\nreactant = \"Sand\"\nother = \"Water\"\nproduct = \"Empty\"\n\
+             other_product = \"Empty\"\nchance = 0.5\nmin_temperature = nan\n",
+            "min_temperature NaN must be finite",
+        );
+    }
+}
@@ -0,0 +1,88 @@
+// Hash-based value noise. Every function here is a pure function of the seed and
+// world coordinate, so chunks generated in any order still line up seamlessly.
+
+pub fn hash2(seed: u64, x: i64, y: i64) -> u64 {
+    let mut h = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+    // splitmix64 finaliser
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+// Maps a hash to [0, 1)
+pub fn hash_to_unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+pub fn value_noise_1d(seed: u64, x: f64) -> f64 {
+    let x0 = x.floor();
+    let t = smoothstep(x - x0);
+    let a = hash_to_unit(hash2(seed, x0 as i64, 0));
+    let b = hash_to_unit(hash2(seed, x0 as i64 + 1, 0));
+    lerp(a, b, t)
+}
+
+pub fn value_noise_2d(seed: u64, x: f64, y: f64) -> f64 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = smoothstep(x - x0);
+    let ty = smoothstep(y - y0);
+    let (ix, iy) = (x0 as i64, y0 as i64);
+
+    let top = lerp(
+        hash_to_unit(hash2(seed, ix, iy)),
+        hash_to_unit(hash2(seed, ix + 1, iy)),
+        tx,
+    );
+    let bottom = lerp(
+        hash_to_unit(hash2(seed, ix, iy + 1)),
+        hash_to_unit(hash2(seed, ix + 1, iy + 1)),
+        tx,
+    );
+    lerp(top, bottom, ty)
+}
+
+// Fractal sum of value noise octaves, normalised back to [0, 1)
+pub fn fbm_1d(seed: u64, x: f64, octaves: u32) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_amplitude = 0.0;
+
+    for octave in 0..octaves {
+        total += amplitude * value_noise_1d(seed.wrapping_add(octave as u64), x * frequency);
+        max_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+
+    total / max_amplitude
+}
+
+pub fn fbm_2d(seed: u64, x: f64, y: f64, octaves: u32) -> f64 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max_amplitude = 0.0;
+
+    for octave in 0..octaves {
+        total += amplitude
+            * value_noise_2d(seed.wrapping_add(octave as u64), x * frequency, y * frequency);
+        max_amplitude += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+
+    total / max_amplitude
+}
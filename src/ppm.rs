@@ -0,0 +1,35 @@
+use crate::chunk_list::ChunkList;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+// Writes every loaded chunk as a binary PPM image, one pixel per tile in the
+// material's colour. Gaps between loaded chunks come out black.
+pub fn write_ppm(path: &Path, chunks: &ChunkList) -> io::Result<()> {
+    let Some(((min_x, min_y), (max_x, max_y))) = chunks.bounds() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "the world has no chunks"));
+    };
+    let chunk_width = chunks.chunk_width;
+    let chunk_height = chunks.chunk_height;
+    let width = (max_x - min_x + 1) as usize * chunk_width;
+    let height = (max_y - min_y + 1) as usize * chunk_height;
+
+    let mut pixels = vec![0u8; width * height * 3];
+    for (&(chunk_x, chunk_y), chunk) in chunks.iter() {
+        let origin_x = (chunk_x - min_x) as usize * chunk_width;
+        let origin_y = (chunk_y - min_y) as usize * chunk_height;
+        for (idx, tile) in chunk.tiles.iter().enumerate() {
+            let x = origin_x + idx % chunk_width;
+            let y = origin_y + idx / chunk_width;
+            let pixel = (y * width + x) * 3;
+            pixels[pixel..pixel + 3].copy_from_slice(&tile.kind.color()[..3]);
+        }
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    write!(out, "P6\n{width} {height}\n255\n")?;
+    out.write_all(&pixels)?;
+    out.flush()
+}
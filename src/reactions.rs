@@ -0,0 +1,96 @@
+use crate::{
+    action::Action,
+    chunk::Chunk,
+    rng::TileRng,
+    tile::Tile,
+    tile_checks::{NEIGHBOR_OFFSETS, offset_tile, tile_at},
+    tiles::tile_kind::TileKind,
+};
+
+// "A touching `other` becomes `product` + `other_product`", stored on the
+// reactant's material. Loaded from the [[reaction]] entries in materials.toml
+// and checked for every tile before its material rules.
+#[derive(Clone, Copy, Debug)]
+pub struct Reaction {
+    pub other: TileKind, // one of the reactant's eight neighbours
+    pub product: TileKind,
+    pub other_product: TileKind,
+    pub chance: f32,                  // probability per step once the two touch
+    pub min_temperature: Option<f32>, // the hotter of the two must be above this
+}
+
+impl Reaction {
+    // Whether the two tiles meet every condition apart from the chance roll
+    fn possible(&self, this_tile: Tile, other_tile: Tile) -> bool {
+        let hottest = this_tile.temperature.max(other_tile.temperature);
+        self.other == other_tile.kind && self.min_temperature.is_none_or(|min| hottest > min)
+    }
+}
+
+// Picks one of the eight surrounding tiles and applies the first reaction
+// between the two that passes its checks
+pub fn react(
+    x: usize,
+    y: usize,
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    rng: &mut TileRng,
+) -> Action {
+    let idx = y * chunk.width + x;
+    let this_tile = chunk.tiles[idx];
+    let reactions = this_tile.kind.reactions();
+    if reactions.is_empty() {
+        return Action::None;
+    }
+
+    let (offset_x, offset_y) = NEIGHBOR_OFFSETS[rng.next_index(NEIGHBOR_OFFSETS.len())];
+    let Some(target) = offset_tile(x, y, chunk, neighbors, offset_x, offset_y) else {
+        return Action::None;
+    };
+    let other_tile = tile_at(chunk, neighbors, target);
+
+    for reaction in reactions {
+        if reaction.possible(this_tile, other_tile) && rng.chance(reaction.chance) {
+            let temperature = shared_temperature(this_tile, other_tile, reaction);
+            return Action::react(
+                idx,
+                Tile {
+                    temperature,
+                    ..Tile::new(reaction.product)
+                },
+                target,
+                Tile {
+                    temperature,
+                    ..Tile::new(reaction.other_product)
+                },
+            );
+        }
+    }
+    Action::None
+}
+
+// Temperature both products start at so that they hold the same heat as the
+// two reactants did; a reaction neither creates nor destroys heat. Keeping
+// each reactant's own temperature instead would let products change straight
+// back, e.g. stone made from lava melting again at the lava's temperature.
+fn shared_temperature(this_tile: Tile, other_tile: Tile, reaction: &Reaction) -> f32 {
+    let heat = this_tile.temperature * this_tile.kind.heat_capacity()
+        + other_tile.temperature * other_tile.kind.heat_capacity();
+    heat / (reaction.product.heat_capacity() + reaction.other_product.heat_capacity())
+}
+
+// Whether some neighbour could react with this tile on a lucky roll
+pub fn can_react(x: usize, y: usize, chunk: &Chunk, neighbors: &[&Chunk]) -> bool {
+    let this_tile = chunk.tiles[y * chunk.width + x];
+    let reactions = this_tile.kind.reactions();
+    if reactions.is_empty() {
+        return false;
+    }
+
+    NEIGHBOR_OFFSETS.iter().any(|&(offset_x, offset_y)| {
+        offset_tile(x, y, chunk, neighbors, offset_x, offset_y).is_some_and(|target| {
+            let other_tile = tile_at(chunk, neighbors, target);
+            reactions.iter().any(|reaction| reaction.possible(this_tile, other_tile))
+        })
+    })
+}
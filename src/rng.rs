@@ -0,0 +1,48 @@
+use crate::noise::hash2;
+
+// Counter-based randomness for the simulation. Every draw is a pure function
+// of the world seed, the tick, the world tile doing the drawing and how many
+// draws that tile has made so far this tick, so a step comes out the same no
+// matter how rayon schedules the chunks.
+pub struct TileRng {
+    key: u64,
+    counter: i64,
+}
+
+impl TileRng {
+    // Shared by every tile in one tick
+    pub fn tick_key(seed: u64, tick: u64) -> u64 {
+        hash2(seed, tick as i64, 0)
+    }
+
+    pub fn new(tick_key: u64, world_x: i64, world_y: i64) -> Self {
+        Self {
+            key: hash2(tick_key, world_x, world_y),
+            counter: 0,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.counter += 1;
+        hash2(self.key, self.counter, 0)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    // Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // Uniform in 0..len
+    pub fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+
+    // True with the given probability
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
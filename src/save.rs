@@ -0,0 +1,329 @@
+use crate::{
+    chunk::Chunk,
+    chunk_list::ChunkList,
+    gravity::Gravity,
+    materials::registry,
+    tile::Tile,
+    tiles::tile_kind::TileKind,
+    world_gen::GeneratorKind,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+// Save files hold every loaded chunk with the full state of each tile, plus
+// what it takes to carry on where the run left off: the generator and seed for
+// chunks not generated yet, the tick the RNG is keyed on, and gravity. All
+// numbers are little-endian. Materials are stored by name, so a save still
+// loads after the materials file gains or reorders entries.
+const MAGIC: &[u8; 8] = b"PWGSAVE\0";
+const VERSION: u32 = 1;
+// Larger chunks or coordinates further out than these can only come from a
+// corrupt file
+const MAX_CHUNK_TILES: usize = 1024 * 1024;
+const MAX_CHUNK_COORD: i32 = 1 << 20;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(String, io::Error),
+    Invalid(String), // not a save file, or one from a different world setup
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(path, error) => write!(f, "could not access {path}: {error}"),
+            SaveError::Invalid(message) => write!(f, "invalid save file: {message}"),
+        }
+    }
+}
+
+pub fn save(path: &Path, chunks: &ChunkList, generator: GeneratorKind) -> Result<(), SaveError> {
+    let io_error = |error| SaveError::Io(path.display().to_string(), error);
+    let file = File::create(path).map_err(io_error)?;
+    let mut out = BufWriter::new(file);
+    write_world(&mut out, chunks, generator)
+        .and_then(|_| out.flush())
+        .map_err(io_error)
+}
+
+pub fn load(path: &Path) -> Result<(ChunkList, GeneratorKind), SaveError> {
+    let file = File::open(path).map_err(|error| SaveError::Io(path.display().to_string(), error))?;
+    read_world(&mut BufReader::new(file)).map_err(|error| match error.kind() {
+        io::ErrorKind::InvalidData => SaveError::Invalid(error.to_string()),
+        io::ErrorKind::UnexpectedEof => SaveError::Invalid("file ends early".to_string()),
+        _ => SaveError::Io(path.display().to_string(), error),
+    })
+}
+
+fn write_world(
+    out: &mut impl Write,
+    chunks: &ChunkList,
+    generator: GeneratorKind,
+) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    write_str(out, generator.name())?;
+    out.write_all(&chunks.seed().to_le_bytes())?;
+    out.write_all(&chunks.tick().to_le_bytes())?;
+    out.write_all(&(chunks.chunk_width as u32).to_le_bytes())?;
+    out.write_all(&(chunks.chunk_height as u32).to_le_bytes())?;
+    out.write_all(&chunks.gravity.direction.0.to_le_bytes())?;
+    out.write_all(&chunks.gravity.direction.1.to_le_bytes())?;
+    out.write_all(&chunks.gravity.strength.to_le_bytes())?;
+
+    let materials: Vec<_> = registry().iter().collect();
+    out.write_all(&(materials.len() as u32).to_le_bytes())?;
+    for (_, material) in materials {
+        write_str(out, &material.name)?;
+    }
+
+    // Sorted so the same world always writes the same file
+    let mut coords: Vec<_> = chunks.iter().map(|(coord, _)| *coord).collect();
+    coords.sort_unstable();
+    out.write_all(&(coords.len() as u32).to_le_bytes())?;
+    for coord in coords {
+        let chunk = chunks.get(&coord).unwrap();
+        out.write_all(&coord.0.to_le_bytes())?;
+        out.write_all(&coord.1.to_le_bytes())?;
+        for tile in &chunk.tiles {
+            out.write_all(&tile.kind.0.to_le_bytes())?;
+            out.write_all(&tile.lifetime.to_le_bytes())?;
+            out.write_all(&tile.temperature.to_le_bytes())?;
+            out.write_all(&tile.velocity.0.to_le_bytes())?;
+            out.write_all(&tile.velocity.1.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn read_world(input: &mut impl Read) -> io::Result<(ChunkList, GeneratorKind)> {
+    let mut magic = [0; 8];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a world save".to_string()));
+    }
+    let version = read_u32(input)?;
+    if version != VERSION {
+        return Err(invalid(format!("unsupported version {version}")));
+    }
+
+    let generator_name = read_str(input)?;
+    let generator = GeneratorKind::from_name(&generator_name)
+        .ok_or_else(|| invalid(format!("unknown generator '{generator_name}'")))?;
+    let seed = read_u64(input)?;
+    let tick = read_u64(input)?;
+    let chunk_width = read_u32(input)? as usize;
+    let chunk_height = read_u32(input)? as usize;
+    let chunk_tiles = match chunk_width.checked_mul(chunk_height) {
+        Some(0) => return Err(invalid("chunks have no tiles".to_string())),
+        Some(tiles) if tiles <= MAX_CHUNK_TILES => tiles,
+        _ => {
+            return Err(invalid(format!(
+                "chunks of {chunk_width}x{chunk_height} tiles are too large"
+            )));
+        }
+    };
+    let gravity = Gravity {
+        direction: (read_i32(input)?, read_i32(input)?),
+        strength: read_f32(input)?,
+    };
+    if !Gravity::CHOICES.iter().any(|(_, direction)| *direction == gravity.direction) {
+        return Err(invalid(format!("unknown gravity direction {:?}", gravity.direction)));
+    }
+    if !gravity.strength.is_finite() {
+        return Err(invalid(format!("gravity strength {} is not a number", gravity.strength)));
+    }
+
+    // Map the ids in the file to the materials loaded now
+    let current: HashMap<&str, TileKind> = registry()
+        .iter()
+        .map(|(kind, material)| (material.name.as_str(), kind))
+        .collect();
+    let material_count = read_u32(input)?;
+    let mut kinds = Vec::new();
+    for _ in 0..material_count {
+        let name = read_str(input)?;
+        match current.get(name.as_str()) {
+            Some(&kind) => kinds.push(kind),
+            None => return Err(invalid(format!("unknown material '{name}'"))),
+        }
+    }
+
+    let chunk_count = read_u32(input)?;
+    let mut chunks = Vec::new();
+    let mut seen = HashSet::new();
+    for _ in 0..chunk_count {
+        let (x, y) = (read_i32(input)?, read_i32(input)?);
+        let in_range = |coord: i32| (-MAX_CHUNK_COORD..=MAX_CHUNK_COORD).contains(&coord);
+        if !in_range(x) || !in_range(y) {
+            return Err(invalid(format!("chunk ({x}, {y}) is too far out")));
+        }
+        if !seen.insert((x, y)) {
+            return Err(invalid(format!("chunk ({x}, {y}) appears twice")));
+        }
+        let mut tiles = Vec::with_capacity(chunk_tiles);
+        for _ in 0..chunk_tiles {
+            let id = read_u16(input)? as usize;
+            let kind = *kinds
+                .get(id)
+                .ok_or_else(|| invalid(format!("material id {id} out of range")))?;
+            tiles.push(Tile {
+                lifetime: read_u16(input)?,
+                temperature: read_f32(input)?,
+                velocity: (read_f32(input)?, read_f32(input)?),
+                ..Tile::new(kind)
+            });
+        }
+        chunks.push(Chunk::from_tiles(chunk_width, chunk_height, x, y, tiles));
+    }
+
+    let mut world = ChunkList::from_chunks(
+        chunk_width,
+        chunk_height,
+        chunks,
+        generator.create(seed),
+        seed,
+        tick,
+    );
+    world.gravity = gravity;
+    Ok((world, generator))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_str(out: &mut impl Write, value: &str) -> io::Result<()> {
+    out.write_all(&(value.len() as u16).to_le_bytes())?;
+    out.write_all(value.as_bytes())
+}
+
+fn read_str(input: &mut impl Read) -> io::Result<String> {
+    let mut bytes = vec![0; read_u16(input)? as usize];
+    input.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|error| invalid(error.to_string()))
+}
+
+fn read_bytes<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u16(input: &mut impl Read) -> io::Result<u16> {
+    read_bytes(input).map(u16::from_le_bytes)
+}
+
+fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    read_bytes(input).map(u32::from_le_bytes)
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    read_bytes(input).map(u64::from_le_bytes)
+}
+
+fn read_i32(input: &mut impl Read) -> io::Result<i32> {
+    read_bytes(input).map(i32::from_le_bytes)
+}
+
+fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    read_bytes(input).map(f32::from_le_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_saved_world_loads_back_tile_for_tile() {
+        let mut world = ChunkList::new(8, 8, 2, 2, GeneratorKind::Terrain.create(3), 3);
+        world.set_tile(5, 5, TileKind::WATER);
+        for _ in 0..20 {
+            world.update();
+            world.cull_chunks(0, 0, 16, 16, 0);
+        }
+
+        let mut bytes = Vec::new();
+        write_world(&mut bytes, &world, GeneratorKind::Terrain).unwrap();
+        let (loaded, generator) = read_world(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!(generator, GeneratorKind::Terrain);
+        assert_eq!((loaded.seed(), loaded.tick()), (3, 20));
+        assert_eq!(loaded.iter().count(), world.iter().count());
+        for (coord, chunk) in world.iter() {
+            assert!(loaded.get(coord).unwrap().tiles == chunk.tiles, "chunk {coord:?}");
+        }
+    }
+
+    // Header up to and including the chunk size
+    fn header(chunk_width: u32, chunk_height: u32) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(5u16.to_le_bytes());
+        bytes.extend(b"empty");
+        bytes.extend(0u64.to_le_bytes()); // seed
+        bytes.extend(0u64.to_le_bytes()); // tick
+        bytes.extend(chunk_width.to_le_bytes());
+        bytes.extend(chunk_height.to_le_bytes());
+        bytes
+    }
+
+    fn read_error(bytes: &[u8]) -> io::Error {
+        match read_world(&mut &bytes[..]) {
+            Ok(_) => panic!("read a world from a broken file"),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn broken_files_are_rejected_instead_of_loaded() {
+        let world = ChunkList::new(8, 8, 1, 1, GeneratorKind::Empty.create(0), 0);
+        let mut bytes = Vec::new();
+        write_world(&mut bytes, &world, GeneratorKind::Empty).unwrap();
+
+        let truncated = read_error(&bytes[..bytes.len() - 3]);
+        assert_eq!(truncated.kind(), io::ErrorKind::UnexpectedEof);
+
+        let oversized = read_error(&header(u32::MAX, u32::MAX));
+        assert_eq!(oversized.kind(), io::ErrorKind::InvalidData);
+        let too_many_tiles = read_error(&header(2048, 1024));
+        assert_eq!(too_many_tiles.kind(), io::ErrorKind::InvalidData);
+
+        // The only chunk is the last thing in the file
+        let mut far_out = bytes.clone();
+        let chunk_start = bytes.len() - (8 + 8 * 8 * 16);
+        far_out[chunk_start..chunk_start + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+        assert_eq!(read_error(&far_out).kind(), io::ErrorKind::InvalidData);
+
+        // Gravity comes straight after the header
+        let gravity_at = header(8, 8).len();
+        for (x, y) in [(2i32, 0i32), (0, -5)] {
+            let mut bad_gravity = bytes[..gravity_at].to_vec();
+            bad_gravity.extend(x.to_le_bytes());
+            bad_gravity.extend(y.to_le_bytes());
+            bad_gravity.extend(&bytes[gravity_at + 8..]);
+            assert_eq!(read_error(&bad_gravity).kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn a_chunk_saved_twice_is_rejected() {
+        let world = ChunkList::new(4, 4, 1, 1, GeneratorKind::Empty.create(0), 0);
+        let mut bytes = Vec::new();
+        write_world(&mut bytes, &world, GeneratorKind::Empty).unwrap();
+
+        // Bump the chunk count and repeat the only chunk
+        let chunk_bytes = 8 + 16 * 16;
+        let chunk_start = bytes.len() - chunk_bytes;
+        let count_at = chunk_start - 4;
+        bytes[count_at..chunk_start].copy_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_within(chunk_start..);
+
+        assert_eq!(read_error(&bytes).kind(), io::ErrorKind::InvalidData);
+    }
+}
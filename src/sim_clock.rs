@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+// Fixed-timestep clock for the simulation. Real time piles up in an
+// accumulator and is paid out in whole ticks of 1 / target_tps seconds, so the
+// simulation runs at the same rate whatever the frame rate: several ticks in
+// one frame when ticks are cheap, none in a frame that comes too soon.
+pub struct SimClock {
+    pub target_tps: f32,
+    accumulator: Duration,
+    last_run: Instant,
+    // Achieved ticks per second, measured over roughly a second
+    window_start: Instant,
+    window_ticks: u32,
+    tps: f32,
+}
+
+impl SimClock {
+    pub const MIN_TPS: f32 = 1.0;
+    pub const MAX_TPS: f32 = 1000.0;
+    // Longest a single call to `run` may spend ticking. When the simulation
+    // can't keep up, the time it is behind by is dropped instead of being
+    // caught up later, so a slow patch doesn't hold everything else up.
+    const TIME_BUDGET: Duration = Duration::from_millis(25);
+
+    pub fn new(target_tps: f32) -> Self {
+        let now = Instant::now();
+        Self {
+            target_tps,
+            accumulator: Duration::ZERO,
+            last_run: now,
+            window_start: now,
+            window_ticks: 0,
+            tps: 0.0,
+        }
+    }
+
+    fn tick_length(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.target_tps.clamp(Self::MIN_TPS, Self::MAX_TPS))
+    }
+
+    // Forgets the time since the last run, e.g. while paused, so resuming
+    // doesn't burst through the ticks that were missed
+    pub fn reset(&mut self) {
+        self.accumulator = Duration::ZERO;
+        self.last_run = Instant::now();
+        self.record_ticks(0);
+    }
+
+    // Time left until the next tick comes due
+    pub fn until_next_tick(&self) -> Duration {
+        self.tick_length()
+            .saturating_sub(self.accumulator + self.last_run.elapsed())
+    }
+
+    // Calls `tick` once for every tick that has come due since the last run
+    pub fn run(&mut self, mut tick: impl FnMut()) {
+        let started = Instant::now();
+        self.accumulator += started - self.last_run;
+        self.last_run = started;
+
+        let tick_length = self.tick_length();
+        let mut ticks = 0;
+        while self.accumulator >= tick_length {
+            if started.elapsed() >= Self::TIME_BUDGET {
+                self.accumulator = Duration::ZERO; // skip what we can't afford
+                break;
+            }
+            tick();
+            self.accumulator -= tick_length;
+            ticks += 1;
+        }
+        self.record_ticks(ticks);
+    }
+
+    fn record_ticks(&mut self, ticks: u32) {
+        self.window_ticks += ticks;
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.tps = self.window_ticks as f32 / elapsed.as_secs_f32();
+            self.window_ticks = 0;
+            self.window_start = Instant::now();
+        }
+    }
+
+    // Ticks per second actually achieved
+    pub fn tps(&self) -> f32 {
+        self.tps
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    chunk_list::{ChunkCoord, ChunkList},
+    gravity::Gravity,
+    math::{div_floor, euclidean_mod},
+    sim_clock::SimClock,
+    tile::Tile,
+    tiles::tile_kind::TileKind,
+};
+
+// The part of the world the UI is looking at, in tiles. Chunks within
+// `buffer_chunks` of it stay loaded and simulated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewRegion {
+    pub tile_x: isize,
+    pub tile_y: isize,
+    pub width_tiles: usize,
+    pub height_tiles: usize,
+    pub buffer_chunks: usize,
+}
+
+// Requests from the UI, applied by the simulation thread in the order sent and
+// always between two ticks
+pub enum SimCommand {
+    SetRunning(bool),
+    SetTargetTps(f32),
+    SetGravity(Gravity),
+    SetView(ViewRegion),
+    Step,
+    Paint(Vec<(isize, isize)>, TileKind), // world tiles to overwrite
+    Shutdown,
+}
+
+// Copy of the visible chunks after some tick, plus the numbers the UI shows.
+// The simulation thread publishes a fresh one after every batch of ticks and
+// never touches it again, so the UI can read it for as long as it likes.
+pub struct Snapshot {
+    pub chunk_width: usize,
+    pub chunk_height: usize,
+    chunks: HashMap<ChunkCoord, Vec<Tile>>,
+    pub chunk_bounds: Option<(ChunkCoord, ChunkCoord)>, // min and max loaded chunk
+    pub awake_chunks: usize,
+    pub loaded_chunks: usize,
+    pub tps: f32,
+}
+
+impl Snapshot {
+    fn capture(chunks: &ChunkList, view: ViewRegion, tps: f32) -> Self {
+        let width = chunks.chunk_width as isize;
+        let height = chunks.chunk_height as isize;
+        let first_x = div_floor(view.tile_x, width) as i32;
+        let first_y = div_floor(view.tile_y, height) as i32;
+        let last_x = div_floor(view.tile_x + view.width_tiles as isize, width) as i32;
+        let last_y = div_floor(view.tile_y + view.height_tiles as isize, height) as i32;
+
+        let mut visible = HashMap::new();
+        for chunk_y in first_y..=last_y {
+            for chunk_x in first_x..=last_x {
+                if let Some(chunk) = chunks.get(&(chunk_x, chunk_y)) {
+                    visible.insert((chunk_x, chunk_y), chunk.tiles.clone());
+                }
+            }
+        }
+
+        Self {
+            chunk_width: chunks.chunk_width,
+            chunk_height: chunks.chunk_height,
+            chunks: visible,
+            chunk_bounds: chunks.bounds(),
+            awake_chunks: chunks.awake_chunks(),
+            loaded_chunks: chunks.alive_chunks.len(),
+            tps,
+        }
+    }
+
+    // Tiles of a visible chunk, row-major
+    pub fn get(&self, coord: &ChunkCoord) -> Option<&[Tile]> {
+        self.chunks.get(coord).map(|tiles| tiles.as_slice())
+    }
+
+    pub fn tile_at(&self, world_x: isize, world_y: isize) -> Option<&Tile> {
+        let chunk_x = div_floor(world_x, self.chunk_width as isize) as i32;
+        let chunk_y = div_floor(world_y, self.chunk_height as isize) as i32;
+        let local_x = euclidean_mod(world_x, self.chunk_width);
+        let local_y = euclidean_mod(world_y, self.chunk_height);
+        self.get(&(chunk_x, chunk_y))
+            .map(|tiles| &tiles[local_y * self.chunk_width + local_x])
+    }
+}
+
+// Owns the world and simulates it on its own thread, so a slow tick never
+// holds up input or rendering. The UI talks to it through commands and reads
+// the latest snapshot.
+pub struct Simulation {
+    commands: Sender<SimCommand>,
+    latest: Arc<Mutex<Arc<Snapshot>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Simulation {
+    pub fn spawn(chunks: ChunkList, view: ViewRegion, running: bool, target_tps: f32) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let latest = Arc::new(Mutex::new(Arc::new(Snapshot::capture(&chunks, view, 0.0))));
+
+        let worker = Worker {
+            chunks,
+            view,
+            running,
+            clock: SimClock::new(target_tps),
+            commands: receiver,
+            latest: Arc::clone(&latest),
+            last_published: Instant::now(),
+        };
+        let worker = std::thread::Builder::new()
+            .name("simulation".to_string())
+            .spawn(move || worker.run())
+            .expect("failed to spawn the simulation thread");
+
+        Self {
+            commands,
+            latest,
+            worker: Some(worker),
+        }
+    }
+
+    pub fn send(&self, command: SimCommand) {
+        // The thread only stops once we are dropped, so this can't fail
+        let _ = self.commands.send(command);
+    }
+
+    pub fn snapshot(&self) -> Arc<Snapshot> {
+        Arc::clone(&self.latest.lock().unwrap())
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        self.send(SimCommand::Shutdown);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+struct Worker {
+    chunks: ChunkList,
+    view: ViewRegion,
+    running: bool,
+    clock: SimClock,
+    commands: Receiver<SimCommand>,
+    latest: Arc<Mutex<Arc<Snapshot>>>,
+    last_published: Instant,
+}
+
+impl Worker {
+    // How long to wait for commands while paused
+    const IDLE_WAIT: Duration = Duration::from_millis(50);
+    // Copying the visible chunks more often than the screen refreshes only
+    // takes time away from ticking
+    const PUBLISH_INTERVAL: Duration = Duration::from_millis(8);
+
+    fn run(mut self) {
+        loop {
+            // Sleep until the next tick is due or a command arrives
+            let wait = if self.running {
+                self.clock.until_next_tick()
+            } else {
+                Self::IDLE_WAIT
+            };
+            let mut changed = false;
+            match self.commands.recv_timeout(wait) {
+                Ok(command) => {
+                    changed = true;
+                    if !self.apply(command) {
+                        return;
+                    }
+                    // Take everything else that is queued before ticking
+                    while let Ok(command) = self.commands.try_recv() {
+                        if !self.apply(command) {
+                            return;
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            if self.running {
+                let (chunks, view) = (&mut self.chunks, self.view);
+                self.clock.run(|| Self::tick(chunks, view));
+            } else {
+                self.clock.reset();
+            }
+            if changed || self.last_published.elapsed() >= Self::PUBLISH_INTERVAL {
+                self.publish();
+            }
+        }
+    }
+
+    // Returns false once the thread should stop
+    fn apply(&mut self, command: SimCommand) -> bool {
+        match command {
+            SimCommand::SetRunning(running) => self.running = running,
+            SimCommand::SetTargetTps(tps) => self.clock.target_tps = tps,
+            SimCommand::SetGravity(gravity) => self.chunks.gravity = gravity,
+            SimCommand::SetView(view) => self.view = view,
+            SimCommand::Step => Self::tick(&mut self.chunks, self.view),
+            SimCommand::Paint(tiles, kind) => {
+                for (x, y) in tiles {
+                    self.chunks.set_tile(x, y, kind);
+                }
+            }
+            SimCommand::Shutdown => return false,
+        }
+        true
+    }
+
+    fn tick(chunks: &mut ChunkList, view: ViewRegion) {
+        chunks.revive_chunks_near_viewport(
+            view.tile_x,
+            view.tile_y,
+            view.width_tiles,
+            view.height_tiles,
+            view.buffer_chunks,
+        );
+        chunks.cull_chunks(
+            view.tile_x,
+            view.tile_y,
+            view.width_tiles,
+            view.height_tiles,
+            view.buffer_chunks,
+        );
+        chunks.update();
+    }
+
+    fn publish(&mut self) {
+        let snapshot = Snapshot::capture(&self.chunks, self.view, self.clock.tps());
+        *self.latest.lock().unwrap() = Arc::new(snapshot);
+        self.last_published = Instant::now();
+    }
+}
@@ -0,0 +1,31 @@
+use crate::tiles::tile_kind::TileKind;
+
+// Temperature everything starts at and open air drifts back towards, in °C
+pub const AMBIENT_TEMPERATURE: f32 = 20.0;
+
+// A tile in a chunk: its material plus whatever state that material needs.
+// Moving a tile moves the whole struct, so state travels with it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub kind: TileKind,
+    pub lifetime: u16, // remaining steps for tiles that expire
+    pub temperature: f32,
+    pub velocity: (f32, f32), // tiles per step, positive y is down
+    pub moved: bool,          // already moved this step, so a later colour phase leaves it be
+}
+
+impl Tile {
+    pub fn new(kind: TileKind) -> Self {
+        Self::with_lifetime(kind, kind.lifetime().unwrap_or(0))
+    }
+
+    pub fn with_lifetime(kind: TileKind, lifetime: u16) -> Self {
+        Self {
+            kind,
+            lifetime,
+            temperature: kind.initial_temperature(),
+            velocity: (0.0, 0.0),
+            moved: false,
+        }
+    }
+}
@@ -1,60 +1,150 @@
-use crate::chunk::Chunk;
+use crate::{chunk::Chunk, gravity::Gravity, tile::Tile};
 
-pub fn below_tile(
+// Directions below are relative to gravity, and all of them return None when
+// gravity is switched off
+
+pub fn below_right_tile(
     x: usize,
     y: usize,
     chunk: &Chunk,
     neighbors: &[&Chunk],
+    gravity: Gravity,
 ) -> Option<(usize, bool, (i32, i32))> {
-    vertical_tile(x, y, chunk, neighbors, true)
+    relative_tile(x, y, chunk, neighbors, gravity, -1, 1)
 }
 
-pub fn below_right_tile(
+pub fn below_left_tile(
     x: usize,
     y: usize,
     chunk: &Chunk,
     neighbors: &[&Chunk],
+    gravity: Gravity,
 ) -> Option<(usize, bool, (i32, i32))> {
-    diagonal_tile(x, y, chunk, neighbors, true, true)
+    relative_tile(x, y, chunk, neighbors, gravity, 1, 1)
 }
 
-pub fn below_left_tile(
+pub fn above_tile(
     x: usize,
     y: usize,
     chunk: &Chunk,
     neighbors: &[&Chunk],
+    gravity: Gravity,
 ) -> Option<(usize, bool, (i32, i32))> {
-    diagonal_tile(x, y, chunk, neighbors, true, false)
+    relative_tile(x, y, chunk, neighbors, gravity, 4, 1)
 }
 
-fn vertical_tile(
+pub fn above_right_tile(
     x: usize,
     y: usize,
     chunk: &Chunk,
     neighbors: &[&Chunk],
-    down: bool,
+    gravity: Gravity,
 ) -> Option<(usize, bool, (i32, i32))> {
+    relative_tile(x, y, chunk, neighbors, gravity, -3, 1)
+}
 
-    let offset_y: i32 = if down { 1 } else { -1 };
-    let offset_x: i32 = 0;
+pub fn above_left_tile(
+    x: usize,
+    y: usize,
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    gravity: Gravity,
+) -> Option<(usize, bool, (i32, i32))> {
+    relative_tile(x, y, chunk, neighbors, gravity, 3, 1)
+}
 
-    get_tile(x, y, chunk, neighbors, offset_x, offset_y)
+pub fn left_tile(
+    x: usize,
+    y: usize,
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    gravity: Gravity,
+) -> Option<(usize, bool, (i32, i32))> {
+    side_tile(x, y, chunk, neighbors, gravity, -1)
 }
 
-fn diagonal_tile(
+pub fn right_tile(
     x: usize,
     y: usize,
     chunk: &Chunk,
     neighbors: &[&Chunk],
-    down: bool,
-    right: bool,
+    gravity: Gravity,
 ) -> Option<(usize, bool, (i32, i32))> {
-    let offset_x: i32 = if right { 1 } else { -1 };
-    let offset_y: i32 = if down { 1 } else { -1 };
+    side_tile(x, y, chunk, neighbors, gravity, 1)
+}
 
+// Offsets of the eight tiles surrounding a tile
+pub const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+// Tile at any offset of at most one chunk away
+pub fn offset_tile(
+    x: usize,
+    y: usize,
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    offset_x: i32,
+    offset_y: i32,
+) -> Option<(usize, bool, (i32, i32))> {
     get_tile(x, y, chunk, neighbors, offset_x, offset_y)
 }
 
+// Tile `steps` tiles to the side, across the direction of gravity
+// (negative = left)
+pub fn side_tile(
+    x: usize,
+    y: usize,
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    gravity: Gravity,
+    steps: i32,
+) -> Option<(usize, bool, (i32, i32))> {
+    if steps < 0 {
+        relative_tile(x, y, chunk, neighbors, gravity, 2, -steps)
+    } else {
+        relative_tile(x, y, chunk, neighbors, gravity, -2, steps)
+    }
+}
+
+// Looks up a tile returned by one of the functions above
+pub fn tile_at(
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    (idx, cross_chunk, coord): (usize, bool, (i32, i32)),
+) -> Tile {
+    if cross_chunk {
+        neighbors
+            .iter()
+            .find(|c| (c.x, c.y) == coord)
+            .unwrap()
+            .tiles[idx]
+    } else {
+        chunk.tiles[idx]
+    }
+}
+
+// Tile `steps` tiles away in the direction `eighths` turns from down
+fn relative_tile(
+    x: usize,
+    y: usize,
+    chunk: &Chunk,
+    neighbors: &[&Chunk],
+    gravity: Gravity,
+    eighths: i32,
+    steps: i32,
+) -> Option<(usize, bool, (i32, i32))> {
+    let (offset_x, offset_y) = gravity.relative(eighths)?;
+    get_tile(x, y, chunk, neighbors, offset_x * steps, offset_y * steps)
+}
+
 fn get_tile(
     x: usize,
     y: usize,
@@ -73,20 +163,22 @@ fn get_tile(
 
     if crossed_y {
         if offset_y > 0 {
-            next_y = 0; // moved down → top row of neighbor
+            next_y -= chunk.height as i32; // moved down → top rows of neighbor
+            chunk_y += 1;
         } else {
-            next_y = chunk.height as i32 - 1; // moved up → bottom row of neighbor
+            next_y += chunk.height as i32; // moved up → bottom rows of neighbor
+            chunk_y -= 1;
         }
-        chunk_y += offset_y;
     }
 
     if crossed_x {
         if offset_x > 0 {
-            next_x = 0; // moved right, wrap to left edge of neighbor
+            next_x -= chunk.width as i32; // moved right, wrap to left side of neighbor
+            chunk_x += 1;
         } else {
-            next_x = chunk.width as i32 - 1; // moved left, wrap to right edge of neighbor
+            next_x += chunk.width as i32; // moved left, wrap to right side of neighbor
+            chunk_x -= 1;
         }
-        chunk_x += offset_x;
     }
 
     let cross_chunk = crossed_y || crossed_x;
@@ -1,12 +0,0 @@
-use crate::colors::Colors;
-
-pub struct Sand;
-pub struct Stone;
-
-impl Sand {
-    pub const COLOR: Colors = Colors::Sand;
-}
-
-impl Stone {
-    pub const COLOR: Colors = Colors::Stone;
-}
\ No newline at end of file
@@ -1,7 +1,5 @@
-use crate::{colors::Colors};
-
 pub struct Empty;
 
 impl Empty {
-    pub const COLOR: Colors = Colors::Black;
-}
\ No newline at end of file
+    pub const COOLING_RATE: f32 = 0.02; // share of the gap to ambient closed each step
+}
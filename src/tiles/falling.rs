@@ -0,0 +1,98 @@
+use crate::{
+    action::Action,
+    bresenham::plot_line,
+    chunk::Chunk,
+    gravity::{Gravity, unit_vector},
+    rng::TileRng,
+    tile_checks::{offset_tile, tile_at},
+};
+
+pub struct Falling;
+
+impl Falling {
+    pub const MAX_SPEED: f32 = 8.0; // must stay below the chunk size
+    pub const DRAG: f32 = 0.8; // share of speed across gravity kept each step
+    pub const REST_SPEED: f32 = 0.01; // slower than this counts as standing still
+
+    // Accelerates the tile along gravity and moves it as far along its velocity
+    // as it can sink, stopping in front of the first obstacle. A tile that lands
+    // turns part of its fall speed (scaled by `splash`) into speed across
+    // gravity in a random direction, which it spends sliding along what it
+    // landed on. Without gravity tiles keep drifting until drag stops them.
+    pub fn fall(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+        splash: f32,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        let this_tile = chunk.tiles[idx];
+
+        // Split the velocity into the part along gravity and the part across it
+        let (down_x, down_y) = gravity.unit();
+        let (velocity_x, velocity_y) = this_tile.velocity;
+        let along = velocity_x * down_x + velocity_y * down_y;
+        let across = (velocity_x - along * down_x, velocity_y - along * down_y);
+
+        let fall_speed = if gravity.is_zero() {
+            0.0
+        } else {
+            (along + gravity.strength).clamp(0.0, Self::MAX_SPEED)
+        };
+        let across = (across.0 * Self::DRAG, across.1 * Self::DRAG);
+        let velocity = (
+            across.0 + fall_speed * down_x,
+            across.1 + fall_speed * down_y,
+        );
+
+        let mut step = (velocity.0.round() as isize, velocity.1.round() as isize);
+        // Anything with gravity falls at least one tile a step
+        if let Some((dx, dy)) = gravity.relative(0)
+            && step.0 * dx as isize + step.1 * dy as isize <= 0
+        {
+            step = (step.0 + dx as isize, step.1 + dy as isize);
+        }
+        let slide = (across.0.round() as isize, across.1.round() as isize);
+
+        let mut paths = vec![(step, velocity)];
+        if slide != (0, 0) && slide != step {
+            paths.push((slide, across)); // slide along the ground
+        }
+
+        for ((end_x, end_y), velocity) in paths {
+            let mut furthest = None;
+            for (offset_x, offset_y) in plot_line(0, 0, end_x, end_y).into_iter().skip(1) {
+                let Some(target) =
+                    offset_tile(x, y, chunk, neighbors, offset_x as i32, offset_y as i32)
+                else {
+                    break;
+                };
+                if !this_tile.kind.sinks_through(tile_at(chunk, neighbors, target).kind) {
+                    break;
+                }
+                furthest = Some(target);
+            }
+            if let Some(target) = furthest {
+                return Action::move_to(idx, target, this_tile, velocity);
+            }
+        }
+
+        // Blocked: settle, splashing across gravity if it hit hard enough to matter
+        let (old_x, old_y) = this_tile.velocity;
+        if old_x.abs() + old_y.abs() > Self::REST_SPEED {
+            let impact = along * splash;
+            let splash_velocity = match gravity.relative(if rng.next_bool() { 2 } else { -2 }) {
+                Some(side) if impact >= 1.0 => {
+                    let (side_x, side_y) = unit_vector(side);
+                    (side_x * impact, side_y * impact)
+                }
+                _ => (0.0, 0.0),
+            };
+            return Action::SetVelocity(idx, splash_velocity);
+        }
+        Action::None
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{
+    action::Action,
+    chunk::Chunk,
+    gravity::Gravity,
+    rng::TileRng,
+    tile::Tile,
+    tile_checks::{NEIGHBOR_OFFSETS, above_tile, offset_tile, tile_at},
+    tiles::tile_kind::TileKind,
+};
+
+pub struct Fire;
+
+impl Fire {
+    pub const SMOKE_CHANCE: f32 = 0.15; // chance per step of puffing smoke upwards
+
+    // Leaves smoke behind once the fuel is used up
+    pub fn burn_out_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        _neighbors: &[&Chunk],
+        _gravity: Gravity,
+        _rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        if chunk.tiles[idx].lifetime == 0 {
+            return Action::Replace(idx, Tile::new(TileKind::SMOKE));
+        }
+        Action::None
+    }
+
+    // Picks one of the eight surrounding tiles and sets it alight with that
+    // material's flammability. The new fire burns for as long as its fuel lasts.
+    pub fn ignite_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        _gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        let (offset_x, offset_y) =
+            NEIGHBOR_OFFSETS[rng.next_index(NEIGHBOR_OFFSETS.len())];
+        if let Some(target) = offset_tile(x, y, chunk, neighbors, offset_x, offset_y) {
+            let fuel = tile_at(chunk, neighbors, target).kind;
+            if fuel.flammability() > 0.0 && rng.chance(fuel.flammability()) {
+                let fire = Tile::with_lifetime(TileKind::FIRE, fuel.burn_time());
+                return Action::replace_at(target, fire);
+            }
+        }
+        Action::None
+    }
+
+    pub fn smoke_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        if let Some(target) = above_tile(x, y, chunk, neighbors, gravity)
+            && tile_at(chunk, neighbors, target).kind == TileKind::EMPTY
+            && rng.chance(Self::SMOKE_CHANCE)
+        {
+            return Action::replace_at(target, Tile::new(TileKind::SMOKE));
+        }
+        Action::None
+    }
+}
@@ -1,10 +1,8 @@
-use crate::{chunk::Chunk, colors::Colors, tiles::tile_kind::TileKind, action::Action};
+use crate::{chunk::Chunk, tile::Tile, tiles::tile_kind::TileKind, action::Action};
 
 pub struct GameOfLife;
 
 impl GameOfLife {
-    pub const COLOR: Colors = Colors::Green;
-
     fn count_live_neighbors(x: usize, y: usize, chunk: &Chunk, neighbors: &[&Chunk]) -> usize {
         let mut count = 0;
 
@@ -59,7 +57,7 @@ impl GameOfLife {
                 // Now safely index into neighbor_chunk
                 if let Some(chunk) = neighbor_chunk {
                     let idx = (local_y * chunk.width as isize + local_x) as usize;
-                    if chunk.tiles[idx].to_colors() == GameOfLife::COLOR {
+                    if chunk.tiles[idx].kind == TileKind::GAME_OF_LIFE {
                         count += 1;
                     }
                 }
@@ -73,7 +71,7 @@ impl GameOfLife {
         let idx = y * chunk.width + x;
         let live_neighbors = GameOfLife::count_live_neighbors(x, y, chunk, neighbors);
         if live_neighbors == 3 {
-            return Action::Replace(idx, TileKind::GameOfLife);
+            return Action::Replace(idx, Tile::new(TileKind::GAME_OF_LIFE));
         }
         Action::None
     }
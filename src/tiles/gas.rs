@@ -0,0 +1,100 @@
+use crate::{
+    action::Action,
+    chunk::Chunk,
+    gravity::Gravity,
+    rng::TileRng,
+    tile_checks::{above_left_tile, above_right_tile, above_tile, left_tile, right_tile, tile_at},
+    tiles::tile_kind::TileKind,
+};
+
+pub struct Gas;
+
+impl Gas {
+    // Gases rise into empty space and bubble up through anything heavier that
+    // would sink into them
+    fn can_rise_into(this_tile: TileKind, other: TileKind) -> bool {
+        other == TileKind::EMPTY || other.sinks_through(this_tile)
+    }
+
+    // Runs first so an expired gas never moves again
+    pub fn dissipate_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        _neighbors: &[&Chunk],
+        _gravity: Gravity,
+        _rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        if chunk.tiles[idx].lifetime == 0 {
+            return Action::Destroy(idx);
+        }
+        Action::None
+    }
+
+    pub fn rise_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        _rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        let this_tile = chunk.tiles[idx];
+        if let Some(target) = above_tile(x, y, chunk, neighbors, gravity)
+            && Self::can_rise_into(this_tile.kind, tile_at(chunk, neighbors, target).kind)
+        {
+            return Action::swap_with(idx, target, this_tile);
+        }
+        Action::None
+    }
+
+    pub fn rise_diagonal_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        let this_tile = chunk.tiles[idx];
+        let rise_right = rng.next_bool();
+        let target = if rise_right {
+            above_right_tile(x, y, chunk, neighbors, gravity)
+        } else {
+            above_left_tile(x, y, chunk, neighbors, gravity)
+        };
+        if let Some(target) = target
+            && Self::can_rise_into(this_tile.kind, tile_at(chunk, neighbors, target).kind)
+        {
+            return Action::swap_with(idx, target, this_tile);
+        }
+        Action::None
+    }
+
+    pub fn spread_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        let this_tile = chunk.tiles[idx];
+        let spread_right = rng.next_bool();
+        let target = if spread_right {
+            right_tile(x, y, chunk, neighbors, gravity)
+        } else {
+            left_tile(x, y, chunk, neighbors, gravity)
+        };
+        if let Some(target) = target
+            && tile_at(chunk, neighbors, target).kind == TileKind::EMPTY
+        {
+            return Action::swap_with(idx, target, this_tile);
+        }
+        Action::None
+    }
+}
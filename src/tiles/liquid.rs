@@ -0,0 +1,83 @@
+use crate::{
+    action::Action,
+    chunk::Chunk,
+    gravity::Gravity,
+    rng::TileRng,
+    tile_checks::{below_left_tile, below_right_tile, side_tile, tile_at},
+    tiles::{falling::Falling, tile_kind::TileKind},
+};
+
+pub struct Liquid;
+
+impl Liquid {
+    pub const SPLASH: f32 = 0.6; // share of fall speed turned sideways on landing
+
+    pub fn fall_down_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        Falling::fall(x, y, chunk, neighbors, gravity, rng, Self::SPLASH)
+    }
+
+    pub fn fall_diagonal_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        let this_tile = chunk.tiles[idx];
+        let first_right = rng.next_bool();
+        for fall_right in [first_right, !first_right] {
+            let target = if fall_right {
+                below_right_tile(x, y, chunk, neighbors, gravity)
+            } else {
+                below_left_tile(x, y, chunk, neighbors, gravity)
+            };
+            if let Some(target) = target
+                && this_tile.kind.sinks_through(tile_at(chunk, neighbors, target).kind)
+            {
+                return Action::swap_with(idx, target, this_tile);
+            }
+        }
+        Action::None
+    }
+
+    // Flows sideways up to the material's dispersion rate, picking the furthest
+    // empty tile in a random direction first. Repeated every step this levels
+    // the surface of liquid sitting in a container.
+    pub fn spread_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        let idx = y * chunk.width + x;
+        let this_tile = chunk.tiles[idx];
+        let first_dir: i32 = if rng.next_bool() { 1 } else { -1 };
+
+        for dir in [first_dir, -first_dir] {
+            let mut furthest = None;
+            for step in 1..=this_tile.kind.dispersion_rate() as i32 {
+                match side_tile(x, y, chunk, neighbors, gravity, dir * step) {
+                    Some(target) if tile_at(chunk, neighbors, target).kind == TileKind::EMPTY => {
+                        furthest = Some(target)
+                    }
+                    _ => break,
+                }
+            }
+            if let Some(target) = furthest {
+                return Action::swap_with(idx, target, this_tile);
+            }
+        }
+        Action::None
+    }
+}
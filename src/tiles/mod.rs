@@ -1,5 +1,8 @@
 pub mod game_of_life;
 pub mod tile_kind;
 pub mod empty;
-pub mod powder;
-pub mod base_elements;
\ No newline at end of file
+pub mod falling;
+pub mod fire;
+pub mod gas;
+pub mod liquid;
+pub mod powder;
\ No newline at end of file
@@ -1,86 +1,80 @@
 use crate::{
-    action::Action, chunk::Chunk, tile_checks::{below_tile, below_right_tile, below_left_tile},
-    tiles::tile_kind::TileKind,
+    action::Action, chunk::Chunk, gravity::Gravity, rng::TileRng,
+    tile_checks::{below_right_tile, below_left_tile, tile_at}, tiles::falling::Falling,
 };
 
 pub struct Powder;
 
 impl Powder {
 
-    pub fn fall_down_rule(x: usize, y: usize, chunk: &Chunk, neighbors: &[&Chunk]) -> Action {
-        let idx = y * chunk.width + x;
-        let this_tile = chunk.tiles[idx];
-        if let Some((below_idx, cross_chunk, destination_chunk  )) = below_tile(x, y, chunk, neighbors) {
-            if cross_chunk {
-                let below_tile = neighbors
-                    .iter()
-                    .find(|c| (c.x, c.y) == destination_chunk)
-                    .unwrap()
-                    .tiles[below_idx];
-                if below_tile == TileKind::Empty {
-                    return Action::SwapCrossChunk(idx, destination_chunk, below_idx, this_tile);
-                }
+    pub const SPLASH: f32 = 0.2; // share of fall speed turned sideways on landing
+
+    pub fn fall_down_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        Falling::fall(x, y, chunk, neighbors, gravity, rng, Self::SPLASH)
+    }
+
+    pub fn fall_diagonal_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        rng: &mut TileRng,
+    ) -> Action {
+        // Try both sides, so a grain that can slide never sits still
+        let first_right = rng.next_bool();
+        for fall_right in [first_right, !first_right] {
+            let action = if fall_right {
+                Self::fall_right_rule(x, y, chunk, neighbors, gravity, rng)
             } else {
-                let below_tile = chunk.tiles[below_idx];
-                if below_tile == TileKind::Empty {
-                    return Action::Swap(idx, below_idx);
-                }
+                Self::fall_left_rule(x, y, chunk, neighbors, gravity, rng)
+            };
+            if !matches!(action, Action::None) {
+                return action;
             }
         }
         Action::None
     }
 
-    pub fn fall_diagonal_rule(x: usize, y: usize, chunk: &Chunk, neighbors: &[&Chunk]) -> Action {
-        let fall_right = rand::random();
-        if fall_right {
-            Self::fall_right_rule(x, y, chunk, neighbors)
-        } else {
-            Self::fall_left_rule(x, y, chunk, neighbors)
-        }
-    }
-
-    fn fall_right_rule(x: usize, y: usize, chunk: &Chunk, neighbors: &[&Chunk]) -> Action {
+    fn fall_right_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        _rng: &mut TileRng,
+    ) -> Action {
         let idx = y * chunk.width + x;
         let this_tile = chunk.tiles[idx];
-        if let Some((below_idx, cross_chunk, destination_chunk)) = below_right_tile(x, y, chunk, neighbors) {
-            if cross_chunk {
-                let below_tile = neighbors
-                    .iter()
-                    .find(|c| (c.x, c.y) == destination_chunk)
-                    .unwrap()
-                    .tiles[below_idx];
-                if below_tile == TileKind::Empty {
-                    return Action::SwapCrossChunk(idx, destination_chunk, below_idx, this_tile);
-                }
-            } else {
-                let below_tile = chunk.tiles[below_idx];
-                if below_tile == TileKind::Empty {
-                    return Action::Swap(idx, below_idx);
-                }
-            }
+        if let Some(target) = below_right_tile(x, y, chunk, neighbors, gravity)
+            && this_tile.kind.sinks_through(tile_at(chunk, neighbors, target).kind)
+        {
+            return Action::swap_with(idx, target, this_tile);
         }
         Action::None
     }
 
-    fn fall_left_rule(x: usize, y: usize, chunk: &Chunk, neighbors: &[&Chunk]) -> Action {
+    fn fall_left_rule(
+        x: usize,
+        y: usize,
+        chunk: &Chunk,
+        neighbors: &[&Chunk],
+        gravity: Gravity,
+        _rng: &mut TileRng,
+    ) -> Action {
         let idx = y * chunk.width + x;
         let this_tile = chunk.tiles[idx];
-        if let Some((below_idx, cross_chunk, destination_chunk)) = below_left_tile(x, y, chunk, neighbors) {
-            if cross_chunk {
-                let below_tile = neighbors
-                    .iter()
-                    .find(|c| (c.x, c.y) == destination_chunk)
-                    .unwrap()
-                    .tiles[below_idx];
-                if below_tile == TileKind::Empty {
-                    return Action::SwapCrossChunk(idx, destination_chunk, below_idx, this_tile);
-                }
-            } else {
-                let below_tile = chunk.tiles[below_idx];
-                if below_tile == TileKind::Empty {
-                    return Action::Swap(idx, below_idx);
-                }
-            }
+        if let Some(target) = below_left_tile(x, y, chunk, neighbors, gravity)
+            && this_tile.kind.sinks_through(tile_at(chunk, neighbors, target).kind)
+        {
+            return Action::swap_with(idx, target, this_tile);
         }
         Action::None
     }
@@ -1,35 +1,156 @@
+use crate::{action::Action, chunk::Chunk, gravity::Gravity, materials::{Behaviour, Material, registry}, reactions::Reaction, rng::TileRng, tiles::{fire::Fire, gas::Gas, liquid::Liquid, powder::Powder}};
 
-use crate::{action::Action, chunk::Chunk, colors::Colors, tiles::{empty::Empty, game_of_life::GameOfLife, powder::Powder, base_elements::{Sand, Stone}}};
+// Picks the action a tile takes this step, if any
+pub type Rule = fn(usize, usize, &Chunk, &[&Chunk], Gravity, &mut TileRng) -> Action;
 
+// Index of a material in the registry loaded from materials.toml
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub enum TileKind {
-    GameOfLife,
-    Empty,
-    Sand,
-    Stone,
-}
+pub struct TileKind(pub u16);
 
 impl TileKind {
-    pub fn to_colors(&self) -> Colors {
-        match self {
-            TileKind::GameOfLife => GameOfLife::COLOR,
-            TileKind::Empty => Empty::COLOR,
-            TileKind::Sand => Sand::COLOR,
-            TileKind::Stone => Stone::COLOR,
-        }
+    // Materials the engine and world generators refer to by name. The registry
+    // hands them these ids whatever order the materials file lists them in.
+    pub const EMPTY: TileKind = TileKind(0);
+    pub const GAME_OF_LIFE: TileKind = TileKind(1);
+    pub const SAND: TileKind = TileKind(2);
+    pub const STONE: TileKind = TileKind(3);
+    pub const COAL: TileKind = TileKind(4);
+    pub const IRON: TileKind = TileKind(5);
+    pub const GOLD: TileKind = TileKind(6);
+    pub const WATER: TileKind = TileKind(7);
+    pub const SMOKE: TileKind = TileKind(8);
+    pub const FIRE: TileKind = TileKind(9);
+
+    // Names of the materials above in id order, with the behaviour the
+    // engine expects of each
+    pub const BUILT_IN: [(&str, Behaviour); 10] = [
+        ("Empty", Behaviour::Static),
+        ("GameOfLife", Behaviour::Life),
+        ("Sand", Behaviour::Powder),
+        ("Stone", Behaviour::Static),
+        ("Coal", Behaviour::Static),
+        ("Iron", Behaviour::Static),
+        ("Gold", Behaviour::Static),
+        ("Water", Behaviour::Liquid),
+        ("Smoke", Behaviour::Gas),
+        ("Fire", Behaviour::Fire),
+    ];
+
+    pub fn material(&self) -> &'static Material {
+        registry().get(*self)
+    }
+
+    pub fn name(&self) -> &'static str {
+        &self.material().name
     }
 
-    pub fn rules(
-        &self,
-    ) -> &'static [fn(usize, usize, &Chunk, &[&Chunk]) -> Action] {
-        match self {
-            TileKind::GameOfLife => &[/*GameOfLife::death_rule*/],
-            TileKind::Empty => &[/*GameOfLife::birth_rule*/],
-            TileKind::Sand => &[Powder::fall_down_rule, Powder::fall_diagonal_rule],
-            TileKind::Stone => &[],
+    pub fn color(&self) -> [u8; 4] {
+        self.material().color
+    }
 
+    pub fn rules(&self) -> &'static [Rule] {
+        match self.material().behaviour {
+            Behaviour::Static => &[],
+            Behaviour::Life => &[/*GameOfLife::death_rule*/],
+            Behaviour::Powder => &[Powder::fall_down_rule, Powder::fall_diagonal_rule],
+            Behaviour::Liquid => &[
+                Liquid::fall_down_rule,
+                Liquid::fall_diagonal_rule,
+                Liquid::spread_rule,
+            ],
+            Behaviour::Gas => &[
+                Gas::dissipate_rule,
+                Gas::rise_rule,
+                Gas::rise_diagonal_rule,
+                Gas::spread_rule,
+            ],
+            Behaviour::Fire => &[Fire::burn_out_rule, Fire::ignite_rule, Fire::smoke_rule],
         }
     }
-}
 
+    // Reactions with this material as the reactant
+    pub fn reactions(&self) -> &'static [Reaction] {
+        &self.material().reactions
+    }
+
+    // Heavier tiles sink through lighter liquids; immovable solids are u8::MAX
+    pub fn density(&self) -> u8 {
+        self.material().density
+    }
+
+    pub fn is_liquid(&self) -> bool {
+        self.material().behaviour == Behaviour::Liquid
+    }
+
+    pub fn is_gas(&self) -> bool {
+        self.material().behaviour == Behaviour::Gas
+    }
+
+    pub fn is_fluid(&self) -> bool {
+        self.is_liquid() || self.is_gas()
+    }
+
+    // Whether this tile swaps places with `other` when `other` is directly or
+    // diagonally below it. Anything that moves falls into empty space and
+    // sinks through lighter liquids and gases; solids and powders hold it up.
+    pub fn sinks_through(&self, other: TileKind) -> bool {
+        let movable = matches!(
+            self.material().behaviour,
+            Behaviour::Powder | Behaviour::Liquid | Behaviour::Gas
+        );
+        movable
+            && (other == TileKind::EMPTY || (other.is_fluid() && other.density() < self.density()))
+    }
 
+    // Steps a freshly placed tile lives for, None if it never expires
+    pub fn lifetime(&self) -> Option<u16> {
+        self.material().lifetime
+    }
+
+    pub fn dispersion_rate(&self) -> usize {
+        self.material().dispersion_rate
+    }
+
+    // Chance per step that a burning neighbour sets this tile alight
+    pub fn flammability(&self) -> f32 {
+        self.material().flammability
+    }
+
+    // Steps the fire lasts after this tile catches
+    pub fn burn_time(&self) -> u16 {
+        self.material().burn_time
+    }
+
+    pub fn initial_temperature(&self) -> f32 {
+        self.material().temperature
+    }
+
+    // Heat sources that keep their temperature no matter what surrounds them
+    pub fn fixed_temperature(&self) -> Option<f32> {
+        let material = self.material();
+        material.hold_temperature.then_some(material.temperature)
+    }
+
+    // Share of a temperature difference passed on to a neighbour per step. A
+    // pair of tiles exchanges heat at the lower of their two conductivities.
+    pub fn conductivity(&self) -> f32 {
+        self.material().conductivity
+    }
+
+    // How much heat it takes to change the temperature by one degree; at least 1.0
+    pub fn heat_capacity(&self) -> f32 {
+        self.material().heat_capacity
+    }
+
+    // What this material turns into at the given temperature, if anything
+    pub fn phase_change(&self, temperature: f32) -> Option<TileKind> {
+        self.material()
+            .transitions
+            .iter()
+            .find(|transition| {
+                transition.below.is_some_and(|below| temperature < below)
+                    || transition.above.is_some_and(|above| temperature >= above)
+            })
+            .map(|transition| transition.into)
+    }
+}
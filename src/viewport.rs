@@ -1,6 +1,8 @@
 use egui::{ColorImage, TextureHandle, TextureOptions};
 
-use crate::{chunk_list::ChunkList, colors::COLORS_RGBA, tiles::tile_kind::TileKind, math::{div_floor, euclidean_mod}};
+use rust_procedural_world_generation::{simulation::{Snapshot, ViewRegion}, tiles::tile_kind::TileKind, math::{div_floor, euclidean_mod}};
+
+use crate::colors::random_alpha;
 
 pub struct Viewport {
     pub width_pixels: usize,
@@ -36,6 +38,17 @@ impl Viewport {
         }
     }
 
+    // The tiles in view, for the simulation to keep loaded
+    pub fn region(&self) -> ViewRegion {
+        ViewRegion {
+            tile_x: self.offset_x / self.scale as isize,
+            tile_y: self.offset_y / self.scale as isize,
+            width_tiles: self.width_tiles,
+            height_tiles: self.height_tiles,
+            buffer_chunks: self.buffer_chunks,
+        }
+    }
+
     pub fn init_texture(&mut self, ctx: &egui::Context) {
         if self.texture.is_none() {
             let empty_image = egui::ColorImage::new(
@@ -47,7 +60,7 @@ impl Viewport {
         }
     }
 
-    pub fn stitch_chunks_for_viewport(&self, alive_chunks: &ChunkList) -> Vec<TileKind> {
+    pub fn stitch_chunks_for_viewport(&self, alive_chunks: &Snapshot) -> Vec<TileKind> {
         let viewport_tiles_x = self.width_tiles + 1;
         let viewport_tiles_y = self.height_tiles + 1;
 
@@ -96,9 +109,9 @@ impl Viewport {
                     };
 
                     if let Some(chunk) = chunk {
-                        let start_idx = row_in_chunk * chunk.width + start_col_in_chunk as usize;
-                        let end_idx = row_in_chunk * chunk.width + end_col_in_chunk as usize;
-                        tiles.extend_from_slice(&chunk.tiles[start_idx..end_idx]);
+                        let start_idx = row_in_chunk * alive_chunks.chunk_width + start_col_in_chunk as usize;
+                        let end_idx = row_in_chunk * alive_chunks.chunk_width + end_col_in_chunk as usize;
+                        tiles.extend(chunk[start_idx..end_idx].iter().map(|tile| tile.kind));
                     }
                 }
             }
@@ -109,7 +122,7 @@ impl Viewport {
 
     pub fn convert_viewport_tiles_to_rgba_buffer(
         &self,
-        alive_chunks: &ChunkList,
+        alive_chunks: &Snapshot,
     ) -> Vec<u8> {
         let mut buffer = vec![0u8; self.width_pixels * self.height_pixels * 4];
         let scale = self.scale as isize;
@@ -132,12 +145,12 @@ impl Viewport {
 
                 let tile = alive_chunks
                     .get(&(chunk_x as i32, chunk_y as i32))
-                    .map(|c| c.tiles[local_y as usize * c.width + local_x as usize])
-                    .unwrap_or(TileKind::Empty);
+                    .map(|c| c[local_y as usize * chunk_w + local_x as usize].kind)
+                    .unwrap_or(TileKind::EMPTY);
 
                 // Convert tile to RGBA
-                let mut color = COLORS_RGBA[tile.to_colors().as_u8() as usize];
-                color[3] = tile.to_colors().random_alpha(world_tile_x as u32, world_tile_y as u32);
+                let mut color = tile.color();
+                color[3] = random_alpha(tile.0 as u32, world_tile_x as u32, world_tile_y as u32);
 
                 // Write to pixel buffer
                 let dst_idx = (pixel_y * self.width_pixels + pixel_x) * 4;
@@ -148,7 +161,7 @@ impl Viewport {
         buffer
     }
 
-    pub fn set_texture_from_chunks(&mut self, ui: &egui::Ui, alive_chunks: &ChunkList) {
+    pub fn set_texture_from_chunks(&mut self, ui: &egui::Ui, alive_chunks: &Snapshot) {
         //let visible_tiles = self.stitch_chunks_for_viewport(alive_chunks);
 
         let viewport_pixels = self.convert_viewport_tiles_to_rgba_buffer(alive_chunks);
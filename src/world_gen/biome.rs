@@ -0,0 +1,123 @@
+use crate::{
+    noise::{fbm_1d, hash_to_unit, hash2},
+    tiles::tile_kind::TileKind,
+};
+
+const BIOME_SALT: u64 = 0x4249_4F4D;
+const DITHER_SALT: u64 = 0x4449_5448;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Biome {
+    Lake,
+    Lowlands,
+    Desert,
+    Highlands,
+}
+
+pub struct BiomeParams {
+    pub surface_offset: f64, // tiles added to the base surface row (positive = lower)
+    pub hill_scale: f64,     // multiplier on the terrain hill height
+    pub top_material: TileKind,
+    pub top_depth: f64, // average thickness of the top layer
+    pub flooded: bool,  // fill dips below the water level with Water
+}
+
+impl Biome {
+    // Ordered by where they sit on the biome noise, low to high
+    pub const ALL: [Biome; 4] = [Biome::Lake, Biome::Lowlands, Biome::Desert, Biome::Highlands];
+
+    // Upper bound of each biome on the biome noise; the last one runs to 1.0
+    const UPPER_BOUNDS: [f64; 3] = [0.38, 0.5, 0.6];
+
+    pub fn params(self) -> BiomeParams {
+        match self {
+            Biome::Lake => BiomeParams {
+                surface_offset: 30.0,
+                hill_scale: 0.3,
+                top_material: TileKind::SAND,
+                top_depth: 6.0,
+                flooded: true,
+            },
+            Biome::Lowlands => BiomeParams {
+                surface_offset: 0.0,
+                hill_scale: 1.0,
+                top_material: TileKind::SAND,
+                top_depth: 6.0,
+                flooded: false,
+            },
+            Biome::Desert => BiomeParams {
+                surface_offset: 5.0,
+                hill_scale: 0.6,
+                top_material: TileKind::SAND,
+                top_depth: 36.0,
+                flooded: false,
+            },
+            Biome::Highlands => BiomeParams {
+                surface_offset: -45.0,
+                hill_scale: 1.6,
+                top_material: TileKind::STONE,
+                top_depth: 0.0,
+                flooded: false,
+            },
+        }
+    }
+}
+
+// Picks biomes from a low-frequency noise along the world x axis. Close to a
+// boundary both biomes get a share of the weight, so terrain parameters can be
+// blended instead of jumping at the border.
+pub struct BiomeMap {
+    seed: u64,
+    pub biome_width: f64, // tiles per noise period
+    pub blend: f64,       // half-width of the blend band, in noise units
+}
+
+impl BiomeMap {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            biome_width: 32.0 * 12.0, // ~12 chunks
+            blend: 0.025,
+        }
+    }
+
+    // Weight of each biome in Biome::ALL order; always sums to 1
+    pub fn weights(&self, world_x: i64) -> [f64; 4] {
+        let n = fbm_1d(self.seed ^ BIOME_SALT, world_x as f64 / self.biome_width, 2);
+        let ramp = |bound: f64| ((n - (bound - self.blend)) / (2.0 * self.blend)).clamp(0.0, 1.0);
+
+        // Telescoping ramps: each biome gets whatever lies above its lower bound
+        // but not above its upper bound
+        let mut weights = [0.0; 4];
+        let mut above_lower = 1.0;
+        for (i, weight) in weights.iter_mut().enumerate() {
+            let above_upper = Biome::UPPER_BOUNDS.get(i).map_or(0.0, |&bound| ramp(bound));
+            *weight = above_lower - above_upper;
+            above_lower = above_upper;
+        }
+        weights
+    }
+
+    pub fn dominant(&self, world_x: i64) -> Biome {
+        let weights = self.weights(world_x);
+        let (best, _) = weights
+            .iter()
+            .enumerate()
+            .fold((0, 0.0), |best, (i, &w)| if w > best.1 { (i, w) } else { best });
+        Biome::ALL[best]
+    }
+
+    // Chooses one biome per tile with probability equal to its weight, which
+    // speckles materials together across a border
+    pub fn dithered(&self, weights: &[f64; 4], world_x: i64, world_y: i64) -> Biome {
+        let roll = hash_to_unit(hash2(self.seed ^ DITHER_SALT, world_x, world_y));
+        let mut cumulative = 0.0;
+        for (i, weight) in weights.iter().enumerate() {
+            cumulative += weight;
+            if roll < cumulative {
+                return Biome::ALL[i];
+            }
+        }
+        Biome::ALL[weights.len() - 1]
+    }
+}
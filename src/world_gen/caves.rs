@@ -0,0 +1,59 @@
+use crate::noise::fbm_2d;
+
+const TUNNEL_SALT: u64 = 0x5455_4E4E;
+const CAVERN_SALT: u64 = 0x4341_5645;
+
+// Carves tunnels and caverns out of solid ground. Tunnels follow the 0.5 contour
+// of a noise field, which gives long connected worms rather than isolated blobs.
+// Both fields are sampled in world coordinates, so a tunnel leaving one chunk
+// carries on in whichever chunk gets generated next to it.
+pub struct CaveCarver {
+    seed: u64,
+    pub tunnel_scale: f64,     // tiles per noise period
+    pub tunnel_width: f64,     // half-width of the band around the contour, in noise units
+    pub cavern_scale: f64,     // tiles per noise period
+    pub cavern_threshold: f64, // noise above this opens into a cavern
+    pub min_depth: i64,        // tiles below the surface before carving starts
+    pub fade_depth: i64,       // tiles over which caves widen to full size
+}
+
+impl CaveCarver {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            tunnel_scale: 96.0,
+            tunnel_width: 0.012,
+            cavern_scale: 64.0,
+            cavern_threshold: 0.72,
+            min_depth: 12,
+            fade_depth: 24,
+        }
+    }
+
+    pub fn is_cave(&self, world_x: i64, world_y: i64, depth: i64) -> bool {
+        if depth < self.min_depth {
+            return false;
+        }
+
+        // Narrow the caves near the surface so only the odd tunnel breaks through
+        let fade = ((depth - self.min_depth) as f64 / self.fade_depth as f64).min(1.0);
+
+        let tunnel = fbm_2d(
+            self.seed ^ TUNNEL_SALT,
+            world_x as f64 / self.tunnel_scale,
+            world_y as f64 / self.tunnel_scale,
+            3,
+        );
+        if (tunnel - 0.5).abs() < self.tunnel_width * fade {
+            return true;
+        }
+
+        let cavern = fbm_2d(
+            self.seed ^ CAVERN_SALT,
+            world_x as f64 / self.cavern_scale,
+            world_y as f64 / self.cavern_scale,
+            3,
+        );
+        cavern > self.cavern_threshold + (1.0 - fade) * 0.2
+    }
+}
@@ -0,0 +1,9 @@
+use crate::{chunk_list::ChunkCoord, tiles::tile_kind::TileKind, world_gen::WorldGenerator};
+
+pub struct EmptyGenerator;
+
+impl WorldGenerator for EmptyGenerator {
+    fn generate(&self, _coord: ChunkCoord, width: usize, height: usize) -> Vec<TileKind> {
+        vec![TileKind::EMPTY; width * height]
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    chunk_list::ChunkCoord,
+    noise::{hash2, hash_to_unit},
+    tiles::tile_kind::TileKind,
+    world_gen::WorldGenerator,
+};
+
+pub struct GameOfLifeSoupGenerator {
+    pub seed: u64,
+    pub density: f64, // chance of each tile starting alive
+}
+
+impl GameOfLifeSoupGenerator {
+    pub fn new(seed: u64, density: f64) -> Self {
+        Self { seed, density }
+    }
+}
+
+impl WorldGenerator for GameOfLifeSoupGenerator {
+    fn generate(&self, coord: ChunkCoord, width: usize, height: usize) -> Vec<TileKind> {
+        let origin_x = coord.0 as i64 * width as i64;
+        let origin_y = coord.1 as i64 * height as i64;
+
+        (0..width * height)
+            .map(|idx| {
+                let world_x = origin_x + (idx % width) as i64;
+                let world_y = origin_y + (idx / width) as i64;
+                if hash_to_unit(hash2(self.seed, world_x, world_y)) < self.density {
+                    TileKind::GAME_OF_LIFE
+                } else {
+                    TileKind::EMPTY
+                }
+            })
+            .collect()
+    }
+}
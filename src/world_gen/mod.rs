@@ -0,0 +1,58 @@
+pub mod biome;
+pub mod caves;
+pub mod empty;
+pub mod game_of_life_soup;
+pub mod ores;
+pub mod structures;
+pub mod terrain;
+
+use crate::{chunk_list::ChunkCoord, tiles::tile_kind::TileKind};
+use biome::Biome;
+use empty::EmptyGenerator;
+use game_of_life_soup::GameOfLifeSoupGenerator;
+use terrain::TerrainGenerator;
+
+// Fills a newly discovered chunk. Implementations must only depend on the chunk
+// coordinate (and their own settings) so chunks can be created in any order.
+pub trait WorldGenerator: Send + Sync {
+    fn generate(&self, coord: ChunkCoord, width: usize, height: usize) -> Vec<TileKind>;
+
+    // Generators without a notion of biomes leave this as None
+    fn biome_at(&self, _world_x: i64, _world_y: i64) -> Option<Biome> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorKind {
+    Empty,
+    GameOfLifeSoup,
+    Terrain,
+}
+
+impl GeneratorKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "empty" => Some(GeneratorKind::Empty),
+            "soup" | "game-of-life" => Some(GeneratorKind::GameOfLifeSoup),
+            "terrain" => Some(GeneratorKind::Terrain),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GeneratorKind::Empty => "empty",
+            GeneratorKind::GameOfLifeSoup => "soup",
+            GeneratorKind::Terrain => "terrain",
+        }
+    }
+
+    pub fn create(self, seed: u64) -> Box<dyn WorldGenerator> {
+        match self {
+            GeneratorKind::Empty => Box::new(EmptyGenerator),
+            GeneratorKind::GameOfLifeSoup => Box::new(GameOfLifeSoupGenerator::new(seed, 0.2)),
+            GeneratorKind::Terrain => Box::new(TerrainGenerator::new(seed)),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{noise::fbm_2d, tiles::tile_kind::TileKind};
+
+pub struct OreVein {
+    pub tile: TileKind,
+    pub salt: u64,
+    pub min_depth: i64,  // tiles below the surface before the ore appears
+    pub full_depth: i64, // depth at which veins reach full thickness
+    pub scale: f64,      // tiles per noise period along the vein
+    pub width: f64,      // half-width of a vein at full depth, in noise units
+    pub coverage: f64,   // fraction of a vein that is actually ore, 0..1
+}
+
+// Scatters ore veins through stone. Like the caves, every decision comes from
+// noise sampled at world coordinates, so a vein crossing a chunk border is
+// continued by whichever chunk is generated on the other side.
+pub struct OreVeins {
+    seed: u64,
+    pub veins: Vec<OreVein>,
+}
+
+impl OreVeins {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            // Checked in order, so rarer ores win where veins overlap
+            veins: vec![
+                OreVein {
+                    tile: TileKind::GOLD,
+                    salt: 0x474F_4C44,
+                    min_depth: 90,
+                    full_depth: 220,
+                    scale: 40.0,
+                    width: 0.008,
+                    coverage: 0.35,
+                },
+                OreVein {
+                    tile: TileKind::IRON,
+                    salt: 0x4952_4F4E,
+                    min_depth: 30,
+                    full_depth: 120,
+                    scale: 56.0,
+                    width: 0.012,
+                    coverage: 0.5,
+                },
+                OreVein {
+                    tile: TileKind::COAL,
+                    salt: 0x434F_414C,
+                    min_depth: 8,
+                    full_depth: 40,
+                    scale: 72.0,
+                    width: 0.018,
+                    coverage: 0.6,
+                },
+            ],
+        }
+    }
+
+    pub fn ore_at(&self, world_x: i64, world_y: i64, depth: i64) -> Option<TileKind> {
+        self.veins
+            .iter()
+            .find(|vein| self.in_vein(vein, world_x, world_y, depth))
+            .map(|vein| vein.tile)
+    }
+
+    fn in_vein(&self, vein: &OreVein, world_x: i64, world_y: i64, depth: i64) -> bool {
+        if depth < vein.min_depth {
+            return false;
+        }
+
+        // Veins thicken with depth until full_depth
+        let depth_factor = ((depth - vein.min_depth) as f64
+            / (vein.full_depth - vein.min_depth).max(1) as f64)
+            .min(1.0);
+
+        // Stretch horizontally so veins run roughly along the strata
+        let seed = self.seed ^ vein.salt;
+        let x = world_x as f64 / vein.scale;
+        let y = world_y as f64 / (vein.scale * 0.5);
+
+        let ridge = fbm_2d(seed, x, y, 3);
+        if (ridge - 0.5).abs() >= vein.width * depth_factor {
+            return false;
+        }
+
+        // Break the contour up into separate deposits
+        let mask = fbm_2d(seed.rotate_left(17), x * 2.0, y * 2.0, 2);
+        mask < 0.4 + vein.coverage * 0.2
+    }
+}
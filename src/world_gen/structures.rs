@@ -0,0 +1,252 @@
+use crate::{
+    chunk_list::ChunkCoord,
+    math::div_floor,
+    noise::{hash_to_unit, hash2},
+    tiles::tile_kind::TileKind,
+};
+
+const CELL_SALT: u64 = 0x5354_5255;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Placement {
+    Surface, // anchor sits on the first ground tile
+    Sky { min_height: i64, max_height: i64 }, // anchor floats this far above the ground
+}
+
+pub struct Structure {
+    pub width: usize,
+    pub height: usize,
+    pub anchor: (usize, usize),       // tile that lands on the chosen world position
+    pub tiles: Vec<Option<TileKind>>, // row-major, None leaves the terrain alone
+    pub placement: Placement,
+    pub weight: u32, // relative chance of being picked for a cell
+}
+
+impl Structure {
+    // Legend: '.' keep terrain, ' ' clear to Empty, '#' Stone, 's' Sand, 'o' Game of Life
+    pub fn from_ascii(
+        placement: Placement,
+        weight: u32,
+        anchor: (usize, usize),
+        rows: &[&str],
+    ) -> Self {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let height = rows.len();
+        let mut tiles = vec![None; width * height];
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                tiles[y * width + x] = match c {
+                    ' ' => Some(TileKind::EMPTY),
+                    '#' => Some(TileKind::STONE),
+                    's' => Some(TileKind::SAND),
+                    'o' => Some(TileKind::GAME_OF_LIFE),
+                    _ => None,
+                };
+            }
+        }
+
+        Self {
+            width,
+            height,
+            anchor,
+            tiles,
+            placement,
+            weight,
+        }
+    }
+}
+
+// A structure at a concrete world position (top-left corner)
+struct PlacedStructure<'a> {
+    structure: &'a Structure,
+    x: i64,
+    y: i64,
+}
+
+// Places at most one structure per column of `cell_width` tiles. Whether a cell
+// gets a structure, which one and where are all hashed from the seed and cell
+// index, so every chunk a structure overlaps works out the same placement and
+// stamps just its own slice of it.
+pub struct StructurePlacer {
+    seed: u64,
+    pub cell_width: i64,
+    pub chance: f64, // probability that a cell holds a structure
+    pub structures: Vec<Structure>,
+}
+
+impl StructurePlacer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            cell_width: 160,
+            chance: 0.4,
+            structures: prefabs(),
+        }
+    }
+
+    fn placement_in_cell(
+        &self,
+        cell: i64,
+        surface: &impl Fn(i64) -> i64,
+    ) -> Option<PlacedStructure<'_>> {
+        let roll = |salt: i64| hash2(self.seed ^ CELL_SALT, cell, salt);
+
+        if hash_to_unit(roll(0)) >= self.chance {
+            return None;
+        }
+
+        let total_weight: u32 = self.structures.iter().map(|s| s.weight).sum();
+        if total_weight == 0 {
+            return None;
+        }
+        let mut pick = (roll(1) % total_weight as u64) as u32;
+        let structure = self.structures.iter().find(|s| {
+            if pick < s.weight {
+                true
+            } else {
+                pick -= s.weight;
+                false
+            }
+        })?;
+
+        let anchor_x = cell * self.cell_width + (roll(2) % self.cell_width as u64) as i64;
+        let ground = surface(anchor_x);
+        let anchor_y = match structure.placement {
+            Placement::Surface => ground,
+            Placement::Sky {
+                min_height,
+                max_height,
+            } => {
+                let span = (max_height - min_height).max(1) as u64;
+                ground - min_height - (roll(3) % span) as i64
+            }
+        };
+
+        Some(PlacedStructure {
+            structure,
+            x: anchor_x - structure.anchor.0 as i64,
+            y: anchor_y - structure.anchor.1 as i64,
+        })
+    }
+
+    pub fn stamp(
+        &self,
+        tiles: &mut [TileKind],
+        coord: ChunkCoord,
+        width: usize,
+        height: usize,
+        surface: impl Fn(i64) -> i64,
+    ) {
+        let chunk_min_x = coord.0 as i64 * width as i64;
+        let chunk_min_y = coord.1 as i64 * height as i64;
+        let chunk_max_x = chunk_min_x + width as i64;
+        let chunk_max_y = chunk_min_y + height as i64;
+
+        // Any cell whose structure could reach into this chunk
+        let reach = self.structures.iter().map(|s| s.width).max().unwrap_or(0) as i64;
+        let first_cell = div_floor((chunk_min_x - reach) as isize, self.cell_width as isize) as i64;
+        let last_cell = div_floor((chunk_max_x + reach) as isize, self.cell_width as isize) as i64;
+
+        for cell in first_cell..=last_cell {
+            let Some(placed) = self.placement_in_cell(cell, &surface) else {
+                continue;
+            };
+            let structure = placed.structure;
+
+            // Intersect the structure with the chunk and copy only that slice
+            let min_x = placed.x.max(chunk_min_x);
+            let max_x = (placed.x + structure.width as i64).min(chunk_max_x);
+            let min_y = placed.y.max(chunk_min_y);
+            let max_y = (placed.y + structure.height as i64).min(chunk_max_y);
+
+            for world_y in min_y..max_y {
+                for world_x in min_x..max_x {
+                    let sx = (world_x - placed.x) as usize;
+                    let sy = (world_y - placed.y) as usize;
+                    if let Some(tile) = structure.tiles[sy * structure.width + sx] {
+                        let lx = (world_x - chunk_min_x) as usize;
+                        let ly = (world_y - chunk_min_y) as usize;
+                        tiles[ly * width + lx] = tile;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn prefabs() -> Vec<Structure> {
+    vec![
+        // Ruined hut, foundation sunk into the ground
+        Structure::from_ascii(
+            Placement::Surface,
+            4,
+            (0, 7),
+            &[
+                ".#.......#...",
+                ".##.....###..",
+                ".# #   # ##..",
+                "##       ##..",
+                "#         #..",
+                "#         ###",
+                "#         # #",
+                "#############",
+                "#############",
+            ],
+        ),
+        // Bridge deck on three pillars
+        Structure::from_ascii(
+            Placement::Surface,
+            3,
+            (10, 6),
+            &[
+                "#####################",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+                "#.........#.........#",
+            ],
+        ),
+        // Game of Life glider
+        Structure::from_ascii(
+            Placement::Sky {
+                min_height: 12,
+                max_height: 40,
+            },
+            2,
+            (1, 1),
+            &[
+                ".o.",
+                "..o",
+                "ooo",
+            ],
+        ),
+        // Gosper glider gun
+        Structure::from_ascii(
+            Placement::Sky {
+                min_height: 20,
+                max_height: 50,
+            },
+            1,
+            (18, 4),
+            &[
+                "........................o...........",
+                "......................o.o...........",
+                "............oo......oo............oo",
+                "...........o...o....oo............oo",
+                "oo........o.....o...oo..............",
+                "oo........o...o.oo....o.o...........",
+                "..........o.....o.......o...........",
+                "...........o...o....................",
+                "............oo......................",
+            ],
+        ),
+    ]
+}
@@ -0,0 +1,133 @@
+use crate::{
+    chunk_list::ChunkCoord,
+    noise::fbm_1d,
+    tiles::tile_kind::TileKind,
+    world_gen::{
+        WorldGenerator,
+        biome::{Biome, BiomeMap},
+        caves::CaveCarver,
+        ores::OreVeins,
+        structures::StructurePlacer,
+    },
+};
+
+// Salts so the height and top layers don't share the same noise
+const HEIGHT_SALT: u64 = 0x4845_4947;
+const TOP_LAYER_SALT: u64 = 0x5341_4E44;
+
+pub struct TerrainGenerator {
+    seed: u64,
+    pub surface_level: i64, // world row of the average surface
+    pub water_level: i64,   // world row that flooded biomes fill up to
+    pub hill_height: f64,   // max tiles above/below surface_level before biome scaling
+    pub hill_width: f64,    // tiles per noise period
+    pub biomes: BiomeMap,
+    pub caves: CaveCarver,
+    pub ores: OreVeins,
+    pub structures: StructurePlacer,
+}
+
+// Per-column terrain shape after blending the biomes that overlap it
+struct Column {
+    surface: i64,
+    top_bottom: i64,
+    flooded: bool,
+    weights: [f64; 4],
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            surface_level: 96,
+            water_level: 116,
+            hill_height: 40.0,
+            hill_width: 160.0,
+            biomes: BiomeMap::new(seed),
+            caves: CaveCarver::new(seed),
+            ores: OreVeins::new(seed),
+            structures: StructurePlacer::new(seed),
+        }
+    }
+
+    fn column(&self, world_x: i64) -> Column {
+        let weights = self.biomes.weights(world_x);
+
+        // Blend the numeric parameters so the surface stays continuous at borders
+        let mut surface_offset = 0.0;
+        let mut hill_scale = 0.0;
+        let mut top_depth = 0.0;
+        let mut flooded = 0.0;
+        for (biome, weight) in Biome::ALL.iter().zip(weights) {
+            let params = biome.params();
+            surface_offset += params.surface_offset * weight;
+            hill_scale += params.hill_scale * weight;
+            top_depth += params.top_depth * weight;
+            if params.flooded {
+                flooded += weight;
+            }
+        }
+
+        let height_noise = fbm_1d(self.seed ^ HEIGHT_SALT, world_x as f64 / self.hill_width, 4);
+        let surface = self.surface_level
+            + surface_offset as i64
+            + ((height_noise - 0.5) * 2.0 * self.hill_height * hill_scale) as i64;
+
+        // Vary the top layer between half and one and a half times the biome depth
+        let depth_noise = fbm_1d(self.seed ^ TOP_LAYER_SALT, world_x as f64 / 48.0, 2);
+        let top_bottom = surface + (top_depth * (0.5 + depth_noise)) as i64;
+
+        Column {
+            surface,
+            top_bottom,
+            flooded: flooded > 0.5,
+            weights,
+        }
+    }
+}
+
+impl WorldGenerator for TerrainGenerator {
+    fn generate(&self, coord: ChunkCoord, width: usize, height: usize) -> Vec<TileKind> {
+        let (chunk_x, chunk_y) = coord;
+        let mut tiles = vec![TileKind::EMPTY; width * height];
+
+        for x in 0..width {
+            // Everything is derived from world coordinates so neighbouring chunks agree
+            let world_x = chunk_x as i64 * width as i64 + x as i64;
+            let column = self.column(world_x);
+
+            for y in 0..height {
+                let world_y = chunk_y as i64 * height as i64 + y as i64;
+
+                tiles[y * width + x] = if world_y < column.surface {
+                    if column.flooded && world_y >= self.water_level {
+                        TileKind::WATER
+                    } else {
+                        TileKind::EMPTY
+                    }
+                } else if world_y < column.top_bottom {
+                    self.biomes
+                        .dithered(&column.weights, world_x, world_y)
+                        .params()
+                        .top_material
+                } else if self.caves.is_cave(world_x, world_y, world_y - column.surface) {
+                    TileKind::EMPTY
+                } else {
+                    self.ores
+                        .ore_at(world_x, world_y, world_y - column.surface)
+                        .unwrap_or(TileKind::STONE)
+                };
+            }
+        }
+
+        // Structures go on last so they can overwrite terrain, caves and ores
+        self.structures
+            .stamp(&mut tiles, coord, width, height, |x| self.column(x).surface);
+
+        tiles
+    }
+
+    fn biome_at(&self, world_x: i64, _world_y: i64) -> Option<Biome> {
+        Some(self.biomes.dominant(world_x))
+    }
+}